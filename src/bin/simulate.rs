@@ -0,0 +1,317 @@
+//! Plays games of a level headless with the AI paddle, as fast as possible,
+//! and prints statistics to help balancing levels without playing them.
+
+use std::{collections::HashSet, process};
+
+use arkanoid_game::game::{
+    AiController, BallLost, Brick, BrickKind, CurrentLevel, Difficulty, GameRng, GameState,
+    GameplayPlugin, Level, RunState, Tuning, TuningHandle, TICKS_PER_SECOND,
+};
+use bevy::{
+    asset::LoadState,
+    ecs::schedule::ExecutorKind,
+    input::InputPlugin,
+    prelude::*,
+    time::{TimeSystem, TimeUpdateStrategy},
+    utils::Instant,
+};
+
+const USAGE: &str = "Usage: simulate [--games N] [--level ID] [--difficulty NAME] [--seed SEED] \
+[--max-seconds SECONDS] [--reaction-delay TICKS] [--max-error PIXELS]";
+
+struct Options {
+    games: u64,
+    level: u32,
+    difficulty: Difficulty,
+    seed: u64,
+    max_seconds: u64,
+    reaction_delay: usize,
+    max_error: f32,
+}
+
+impl Options {
+    fn from_args() -> Result<Self, String> {
+        let ai = AiController::default();
+        let mut options = Options {
+            games: 100,
+            level: CurrentLevel::default().0,
+            difficulty: Difficulty::default(),
+            seed: 0,
+            max_seconds: 600,
+            reaction_delay: ai.reaction_delay,
+            max_error: ai.max_error,
+        };
+
+        let mut args = std::env::args().skip(1);
+        while let Some(flag) = args.next() {
+            let value = args
+                .next()
+                .ok_or_else(|| format!("Missing value for {}", flag))?;
+            let invalid = || format!("Invalid value for {}: {}", flag, value);
+
+            match flag.as_str() {
+                "--games" => options.games = value.parse().map_err(|_| invalid())?,
+                "--level" => options.level = value.parse().map_err(|_| invalid())?,
+                "--difficulty" => {
+                    options.difficulty = Difficulty::ALL
+                        .into_iter()
+                        .find(|difficulty| difficulty.name().eq_ignore_ascii_case(&value))
+                        .ok_or_else(invalid)?
+                }
+                "--seed" => options.seed = value.parse().map_err(|_| invalid())?,
+                "--max-seconds" => options.max_seconds = value.parse().map_err(|_| invalid())?,
+                "--reaction-delay" => {
+                    options.reaction_delay = value.parse().map_err(|_| invalid())?
+                }
+                "--max-error" => options.max_error = value.parse().map_err(|_| invalid())?,
+                _ => return Err(format!("Unknown option {}", flag)),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+struct GameResult {
+    cleared: bool,
+    ticks: u64,
+    balls_lost: u32,
+    score: i32,
+    untouched_cells: HashSet<(usize, usize)>,
+}
+
+/// Balls lost in the game being played.
+#[derive(Resource, Default)]
+struct BallsLost(u32);
+
+fn count_balls_lost(
+    mut ball_lost_events: EventReader<BallLost>,
+    mut balls_lost: ResMut<BallsLost>,
+) {
+    balls_lost.0 += ball_lost_events.iter().count() as u32;
+}
+
+/// Moves the clock forward by exactly one gameplay tick every update, so
+/// games run as fast as the CPU allows.
+fn advance_clock(mut strategy: ResMut<TimeUpdateStrategy>, fixed_time: Res<FixedTime>) {
+    if let TimeUpdateStrategy::ManualInstant(instant) = strategy.as_mut() {
+        *instant += fixed_time.period;
+    }
+}
+
+fn main() {
+    let options = Options::from_args().unwrap_or_else(|err| {
+        eprintln!("{}\n{}", err, USAGE);
+        process::exit(2);
+    });
+
+    let Some(level) = Level::get(options.level) else {
+        eprintln!("Unknown level {}", options.level);
+        process::exit(2);
+    };
+
+    let mut app = headless_app(level, options.difficulty);
+    let results: Vec<GameResult> = (0..options.games)
+        .map(|game| run_game(&mut app, &options, options.seed.wrapping_add(game)))
+        .collect();
+
+    print_report(level, &options, &results);
+}
+
+fn headless_app(level: &Level, difficulty: Difficulty) -> App {
+    let mut app = App::new();
+
+    app.add_plugins(MinimalPlugins)
+        .add_plugin(AssetPlugin::default())
+        .add_plugin(InputPlugin)
+        .add_asset::<Mesh>()
+        .add_asset::<ColorMaterial>()
+        .insert_resource(CurrentLevel(level.id))
+        .insert_resource(difficulty)
+        .add_plugin(GameplayPlugin)
+        .init_resource::<BallsLost>()
+        .add_system(count_balls_lost)
+        .insert_resource(TimeUpdateStrategy::ManualInstant(Instant::now()))
+        .add_system(advance_clock.before(TimeSystem).in_base_set(CoreSet::First))
+        .edit_schedule(CoreSchedule::Main, |schedule| {
+            // Nothing runs in parallel within a tick, threads only add overhead.
+            schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+        });
+
+    // The first update has no elapsed time to simulate.
+    app.update();
+
+    // Play every game with the tuning file rather than the defaults.
+    loop {
+        let handle = app.world.resource::<TuningHandle>().0.clone();
+        match app.world.resource::<AssetServer>().get_load_state(&handle) {
+            LoadState::Loaded if app.world.resource::<Assets<Tuning>>().contains(&handle) => {
+                app.update();
+                break;
+            }
+            LoadState::Failed => {
+                eprintln!("Could not load the tuning file, using the defaults");
+                break;
+            }
+            _ => {
+                std::thread::yield_now();
+                app.update();
+            }
+        }
+    }
+
+    app
+}
+
+fn run_game(app: &mut App, options: &Options, seed: u64) -> GameResult {
+    app.world.resource_mut::<GameRng>().reseed(seed);
+    app.insert_resource(BallsLost(0));
+    app.insert_resource(AiController::new(
+        options.reaction_delay,
+        options.max_error,
+        seed,
+    ));
+    app.world
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::Playing);
+
+    let max_ticks = options.max_seconds * TICKS_PER_SECOND as u64;
+    let mut cleared = false;
+    let mut untouched_cells = HashSet::new();
+
+    loop {
+        app.update();
+
+        // Clearing or warping out of the level moves the run on to the next
+        // level's bricks, or ends it.
+        let run = app.world.resource::<RunState>();
+        if run.cleared || run.level != options.level {
+            cleared = true;
+            break;
+        }
+
+        untouched_cells = app
+            .world
+            .query::<&Brick>()
+            .iter(&app.world)
+            .filter(|brick| !brick.is_damaged())
+            .map(|brick| brick.cell)
+            .collect();
+
+        let game_over = app.world.resource::<NextState<GameState>>().0.is_some();
+        if game_over || app.world.resource::<RunState>().elapsed_ticks >= max_ticks {
+            break;
+        }
+    }
+
+    let run = app.world.resource::<RunState>();
+    let result = GameResult {
+        cleared,
+        ticks: run.elapsed_ticks,
+        balls_lost: app.world.resource::<BallsLost>().0,
+        score: run.score,
+        untouched_cells,
+    };
+
+    app.world
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::GameOver);
+    app.update();
+
+    result
+}
+
+fn print_report(level: &Level, options: &Options, results: &[GameResult]) {
+    let games = results.len().max(1) as f32;
+    let seconds = |ticks: u64| ticks as f32 / TICKS_PER_SECOND as f32;
+
+    println!(
+        "Level {} \"{}\" on {}: {} games, AI reaction delay {} ticks, max error {}px\n",
+        level.id,
+        level.name,
+        options.difficulty.name(),
+        results.len(),
+        options.reaction_delay,
+        options.max_error
+    );
+
+    let clear_times: Vec<f32> = results
+        .iter()
+        .filter(|result| result.cleared)
+        .map(|result| seconds(result.ticks))
+        .collect();
+    println!(
+        "Cleared:          {} / {}",
+        clear_times.len(),
+        results.len()
+    );
+    if clear_times.is_empty() {
+        println!("Time to clear:    -");
+    } else {
+        println!(
+            "Time to clear:    avg {:.1}s (min {:.1}s, max {:.1}s)",
+            clear_times.iter().sum::<f32>() / clear_times.len() as f32,
+            clear_times.iter().cloned().fold(f32::INFINITY, f32::min),
+            clear_times.iter().cloned().fold(0., f32::max),
+        );
+    }
+
+    let balls_lost: u32 = results.iter().map(|result| result.balls_lost).sum();
+    println!(
+        "Balls lost:       avg {:.2} per game ({} total)",
+        balls_lost as f32 / games,
+        balls_lost
+    );
+
+    let mut scores: Vec<i32> = results.iter().map(|result| result.score).collect();
+    scores.sort_unstable();
+    if let (Some(min), Some(max)) = (scores.first(), scores.last()) {
+        let percentile = |p: usize| scores[(scores.len() - 1) * p / 100];
+        println!(
+            "Score:            min {}, p25 {}, median {}, p75 {}, max {}, mean {:.1}",
+            min,
+            percentile(25),
+            percentile(50),
+            percentile(75),
+            max,
+            scores.iter().sum::<i32>() as f32 / games,
+        );
+    }
+
+    // A brick was never hit if it was left untouched in every game.
+    let never_hit = |cell: &(usize, usize)| {
+        results
+            .iter()
+            .all(|result| result.untouched_cells.contains(cell))
+    };
+    let bricks: Vec<(usize, usize)> = level
+        .layout
+        .iter()
+        .enumerate()
+        .flat_map(|(row, cells)| {
+            cells
+                .chars()
+                .enumerate()
+                .filter(|(_, cell)| BrickKind::from_layout_char(*cell).is_some())
+                .map(move |(column, _)| (row, column))
+        })
+        .collect();
+    println!(
+        "Bricks never hit: {} of {} (# never hit, o hit)",
+        bricks.iter().filter(|cell| never_hit(cell)).count(),
+        bricks.len()
+    );
+
+    for (row, cells) in level.layout.iter().enumerate() {
+        let line: String = cells
+            .chars()
+            .enumerate()
+            .map(|(column, cell)| match BrickKind::from_layout_char(cell) {
+                Some(_) if never_hit(&(row, column)) => '#',
+                Some(_) => 'o',
+                None => '.',
+            })
+            .collect();
+        println!("  {}", line);
+    }
+}
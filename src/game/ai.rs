@@ -0,0 +1,219 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use super::{
+    ball_bounds, Arena, Ball, BossProjectile, GameState, GameplaySet, Player, PlayerActions,
+    Settings, WALL_THICKNESS,
+};
+
+/// Distance from the predicted landing point under which the paddle is
+/// considered on target.
+const AI_DEADZONE: f32 = 4.;
+/// Distance from the predicted landing point above which the AI boosts.
+const AI_BOOST_DISTANCE: f32 = 120.;
+/// Ticks ahead the AI looks for boss projectiles about to hit the paddle.
+const AI_DODGE_TICKS: f32 = 90.;
+/// Room the AI leaves between the paddle and a projectile it dodges.
+const AI_DODGE_MARGIN: f32 = 30.;
+
+pub struct AiPlugin;
+
+impl Plugin for AiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(
+            drive_paddle
+                .run_if(
+                    resource_exists::<AiController>().and_then(not(in_state(GameState::Replaying))),
+                )
+                .in_set(GameplaySet::ReadActions)
+                .in_schedule(CoreSchedule::FixedUpdate),
+        )
+        .add_system(toggle_autoplay.in_set(OnUpdate(GameState::Playing)))
+        .add_system(start_autoplay.in_schedule(OnEnter(GameState::Playing)));
+    }
+}
+
+/// Drives the paddle instead of the keyboard while the resource exists.
+///
+/// The AI keeps its own random number generator so it does not consume
+/// numbers from [`GameRng`](super::GameRng), which would desync replays of
+/// AI driven runs.
+#[derive(Resource)]
+pub struct AiController {
+    /// Number of ticks between the ball moving and the AI seeing it.
+    pub reaction_delay: usize,
+    /// Largest distance, in pixels, the AI may misjudge the landing point by.
+    pub max_error: f32,
+    rng: ChaCha8Rng,
+    observations: VecDeque<(Vec2, (i32, i32))>,
+    last_direction: (i32, i32),
+    error: f32,
+    wiggle: bool,
+}
+
+impl AiController {
+    pub fn new(reaction_delay: usize, max_error: f32, seed: u64) -> Self {
+        Self {
+            reaction_delay,
+            max_error,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            observations: VecDeque::new(),
+            last_direction: (0, 0),
+            error: 0.,
+            wiggle: false,
+        }
+    }
+
+    /// Never misses as long as the paddle is fast enough.
+    pub fn perfect(seed: u64) -> Self {
+        Self::new(0, 0., seed)
+    }
+
+    /// Reacts like a distracted human and misses every now and then.
+    pub fn casual(seed: u64) -> Self {
+        Self::new(12, 45., seed)
+    }
+}
+
+impl Default for AiController {
+    fn default() -> Self {
+        Self::new(4, 15., 0)
+    }
+}
+
+/// Predicts where the ball centre will be horizontally once it comes down to
+/// `target_y`, bouncing off `bounds` the way `update_ball_direction` does.
+/// Bricks are ignored.
+pub fn predict_ball_x(position: Vec2, direction: (i32, i32), target_y: f32, bounds: Vec2) -> f32 {
+    let vertical_distance = match direction.1 {
+        1 => (bounds.y - position.y) + (bounds.y - target_y),
+        -1 if position.y >= target_y => position.y - target_y,
+        _ => return position.x,
+    };
+
+    // Unfold the bounces: the ball moves one unit sideways per unit down.
+    let width = bounds.x * 2.;
+    let unfolded = position.x + bounds.x + direction.0 as f32 * vertical_distance;
+    let folded = unfolded.rem_euclid(width * 2.);
+
+    if folded > width {
+        width * 2. - folded - bounds.x
+    } else {
+        folded - bounds.x
+    }
+}
+
+/// Where the first boss projectile to come down on the paddle within
+/// [`AI_DODGE_TICKS`] lands, if any.
+fn incoming_projectile_x(
+    projectiles: impl Iterator<Item = (Vec2, Vec2)>,
+    paddle: Vec2,
+    clearance: f32,
+) -> Option<f32> {
+    projectiles
+        .filter(|(_, velocity)| velocity.y < 0.)
+        .map(|(position, velocity)| {
+            let ticks = (position.y - paddle.y) / -velocity.y;
+            (ticks, position.x + velocity.x * ticks)
+        })
+        .filter(|&(ticks, x)| {
+            (0. ..=AI_DODGE_TICKS).contains(&ticks) && (x - paddle.x).abs() < clearance
+        })
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, x)| x)
+}
+
+fn drive_paddle(
+    mut ai: ResMut<AiController>,
+    mut actions: ResMut<PlayerActions>,
+    ball_query: Query<(&Ball, &Transform)>,
+    player_query: Query<(&Player, &Transform)>,
+    projectiles_query: Query<(&BossProjectile, &Transform)>,
+    arena: Res<Arena>,
+) {
+    let (ball, ball_transform) = ball_query.get_single().unwrap();
+    let (player, player_transform) = player_query.get_single().unwrap();
+
+    ai.observations
+        .push_back((ball_transform.translation.truncate(), ball.direction));
+    while ai.observations.len() > ai.reaction_delay + 1 {
+        ai.observations.pop_front();
+    }
+    let (position, direction) = ai.observations[0];
+
+    // Misjudge each new trajectory by a fresh random amount.
+    if direction != ai.last_direction {
+        ai.last_direction = direction;
+        let max_error = ai.max_error;
+        ai.error = if max_error > 0. {
+            ai.rng.gen_range(-max_error..=max_error)
+        } else {
+            0.
+        };
+    }
+
+    let target_y = player_transform.translation.y + player.get_size().y / 2. + ball.get_radius();
+    let mut target_x =
+        predict_ball_x(position, direction, target_y, ball_bounds(&arena, ball)) + ai.error;
+
+    // Stepping out of the way of a boss projectile comes before the ball.
+    let paddle = player_transform.translation.truncate();
+    let clearance = player.get_size().x / 2. + AI_DODGE_MARGIN;
+    let projectiles = projectiles_query
+        .iter()
+        .map(|(projectile, transform)| (transform.translation.truncate(), projectile.velocity));
+    if let Some(landing_x) = incoming_projectile_x(projectiles, paddle, clearance) {
+        let limit = arena.half_size().x - WALL_THICKNESS - player.get_size().x / 2.;
+        let side = if paddle.x >= landing_x { 1. } else { -1. };
+        target_x = landing_x + side * clearance;
+        if target_x.abs() > limit {
+            target_x = landing_x - side * clearance;
+        }
+    }
+
+    let distance = target_x - player_transform.translation.x;
+
+    // The paddle keeps sliding without input, so hold position by nudging it
+    // back and forth.
+    let go_right = if distance.abs() <= AI_DEADZONE {
+        ai.wiggle = !ai.wiggle;
+        ai.wiggle
+    } else {
+        distance > 0.
+    };
+
+    *actions = PlayerActions {
+        left: !go_right,
+        right: go_right,
+        boost: distance.abs() > AI_BOOST_DISTANCE,
+        // Lasers only fire while the paddle has them, as often as they can.
+        fire: true,
+        launch: true,
+    };
+}
+
+fn toggle_autoplay(
+    mut commands: Commands,
+    keyboard_input: Res<Input<KeyCode>>,
+    settings: Res<Settings>,
+    ai: Option<Res<AiController>>,
+) {
+    if !keyboard_input.just_pressed(settings.controls.toggle_autoplay) {
+        return;
+    }
+
+    if ai.is_some() {
+        commands.remove_resource::<AiController>();
+    } else {
+        commands.insert_resource(AiController::default());
+    }
+}
+
+fn start_autoplay(mut commands: Commands, settings: Res<Settings>) {
+    if settings.gameplay.autoplay {
+        commands.insert_resource(AiController::default());
+    }
+}
@@ -0,0 +1,179 @@
+//! Walls along the left, right and top edges of the arena.
+//!
+//! Walls are colliders like bricks, so the ball bounces off them through the
+//! same collision path and they can be moved, resized or left out to shape
+//! the arena. The bottom of the right wall is a warp gate the Break power-up
+//! opens, letting the paddle out of the level.
+
+use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
+
+use super::{
+    Arena, Collider, GameplaySet, LevelAdvanced, PaddleWarped, Player, PowerUpCollected,
+    PowerUpKind, Skin, ThemePart, RUN_STATES,
+};
+
+/// Width of the side walls and height of the top wall.
+pub const WALL_THICKNESS: f32 = 16.;
+/// Height of the warp gate, from the bottom of the arena. Leaves room for
+/// the paddle.
+const GATE_HEIGHT: f32 = 140.;
+
+const WALL_COLOR: Color = Color::GRAY;
+
+pub struct ArenaPlugin;
+
+impl Plugin for ArenaPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(initialize)
+            .add_system(
+                open_warp_gate
+                    .in_set(GameplaySet::Cleanup)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            )
+            .add_system(
+                warp_paddle
+                    .in_set(GameplaySet::Collision)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            )
+            .add_system(
+                close_warp_gate
+                    .run_if(on_event::<LevelAdvanced>())
+                    .in_set(GameplaySet::NextLevel)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            );
+
+        for state in RUN_STATES {
+            app.add_system(close_warp_gate.in_schedule(OnExit(state)));
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum WallSide {
+    Left,
+    Right,
+    Top,
+}
+
+#[derive(Component)]
+pub struct ArenaWall {
+    pub side: WallSide,
+}
+
+/// A wall segment the paddle can go through once open. The ball still
+/// bounces off it, so it cannot be lost through the gate.
+#[derive(Component, Default)]
+pub struct WarpGate {
+    pub open: bool,
+}
+
+/// Centre and size of the wall segments: the left wall, the right wall
+/// above the gate, the top wall spanning the whole width, and the gate.
+fn wall_boxes(arena: &Arena) -> [(WallSide, Vec2, Vec2, bool); 4] {
+    let half = arena.half_size();
+    let inset = half - WALL_THICKNESS / 2.;
+    let right_height = arena.size.y - GATE_HEIGHT;
+
+    [
+        (
+            WallSide::Left,
+            Vec2::new(-inset.x, 0.),
+            Vec2::new(WALL_THICKNESS, arena.size.y),
+            false,
+        ),
+        (
+            WallSide::Right,
+            Vec2::new(inset.x, half.y - right_height / 2.),
+            Vec2::new(WALL_THICKNESS, right_height),
+            false,
+        ),
+        (
+            WallSide::Top,
+            Vec2::new(0., inset.y),
+            Vec2::new(arena.size.x, WALL_THICKNESS),
+            false,
+        ),
+        (
+            WallSide::Right,
+            Vec2::new(inset.x, -half.y + GATE_HEIGHT / 2.),
+            Vec2::new(WALL_THICKNESS, GATE_HEIGHT),
+            true,
+        ),
+    ]
+}
+
+fn initialize(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    arena: Res<Arena>,
+) {
+    let mesh = meshes.add(shape::Quad::new(Vec2::ONE).into());
+    let material = materials.add(ColorMaterial::from(WALL_COLOR));
+
+    for (side, position, size, gate) in wall_boxes(&arena) {
+        let mut wall = commands.spawn((
+            MaterialMesh2dBundle {
+                mesh: mesh.clone().into(),
+                material: material.clone(),
+                transform: Transform::from_translation(position.extend(0.))
+                    .with_scale(size.extend(1.)),
+                ..default()
+            },
+            ArenaWall { side },
+            Collider { size },
+            Skin {
+                part: ThemePart::Wall,
+                size: Vec2::ONE,
+            },
+        ));
+
+        if gate {
+            wall.insert((WarpGate::default(), Name::new("WarpGate")));
+        } else {
+            wall.insert(Name::new(format!("Wall-{:?}", side)));
+        }
+    }
+}
+
+fn open_warp_gate(
+    mut power_up_collected_events: EventReader<PowerUpCollected>,
+    mut gates_query: Query<(&mut WarpGate, &mut Visibility)>,
+) {
+    let opened = power_up_collected_events
+        .iter()
+        .any(|power_up| power_up.kind == PowerUpKind::Break);
+    if !opened {
+        return;
+    }
+
+    for (mut gate, mut visibility) in gates_query.iter_mut() {
+        gate.open = true;
+        *visibility = Visibility::Hidden;
+    }
+}
+
+fn close_warp_gate(mut gates_query: Query<(&mut WarpGate, &mut Visibility)>) {
+    for (mut gate, mut visibility) in gates_query.iter_mut() {
+        gate.open = false;
+        *visibility = Visibility::Inherited;
+    }
+}
+
+/// Ends the level once the paddle is all the way into an open gate.
+fn warp_paddle(
+    player_query: Query<(&Player, &Transform)>,
+    gates_query: Query<&WarpGate>,
+    arena: Res<Arena>,
+    mut paddle_warped_event: EventWriter<PaddleWarped>,
+) {
+    if !gates_query.iter().any(|gate| gate.open) {
+        return;
+    }
+
+    let (player, transform) = player_query.single();
+    let left_edge = transform.translation.x - player.get_size().x / 2.;
+    if left_edge >= arena.half_size().x - WALL_THICKNESS {
+        paddle_warped_event.send(PaddleWarped);
+    }
+}
@@ -0,0 +1,199 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    reset_run, BallLost, BrickDestroyed, BrickHit, GameState, Level, LevelAdvanced, PaddleHit,
+    PowerUpCollected, RunState, WallBounce, RUN_STATES,
+};
+
+pub struct GameAudioPlugin;
+
+impl Plugin for GameAudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<VolumeSettings>()
+            .init_resource::<LevelMusic>()
+            .add_startup_system(load_sound_effects)
+            .add_system(play_sound_effects)
+            .add_system(apply_music_volume)
+            .add_system(switch_level_music)
+            .add_system(play_game_over_sound.in_schedule(OnEnter(GameState::GameOver)));
+
+        for state in RUN_STATES {
+            app.add_system(
+                start_level_music
+                    .after(reset_run)
+                    .in_schedule(OnEnter(state.clone())),
+            )
+            .add_system(stop_level_music.in_schedule(OnExit(state)));
+        }
+    }
+}
+
+/// Volumes between `0.` and `1.`. Music and sound effects are both scaled
+/// by `master`.
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(default)]
+pub struct VolumeSettings {
+    pub master: f32,
+    pub music: f32,
+    pub sfx: f32,
+}
+
+impl Default for VolumeSettings {
+    fn default() -> Self {
+        Self {
+            master: 1.,
+            music: 0.5,
+            sfx: 0.8,
+        }
+    }
+}
+
+impl VolumeSettings {
+    fn music_volume(&self) -> f32 {
+        self.master * self.music
+    }
+
+    fn sfx_volume(&self) -> f32 {
+        self.master * self.sfx
+    }
+}
+
+#[derive(Resource)]
+struct SoundEffects {
+    paddle_hit: Handle<AudioSource>,
+    brick_hit: Handle<AudioSource>,
+    brick_destroy: Handle<AudioSource>,
+    wall_bounce: Handle<AudioSource>,
+    ball_lost: Handle<AudioSource>,
+    power_up: Handle<AudioSource>,
+    game_over: Handle<AudioSource>,
+}
+
+#[derive(Resource, Default)]
+struct LevelMusic(Option<Handle<AudioSink>>);
+
+fn load_sound_effects(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(SoundEffects {
+        paddle_hit: asset_server.load("sounds/paddle_hit.wav"),
+        brick_hit: asset_server.load("sounds/brick_hit.wav"),
+        brick_destroy: asset_server.load("sounds/brick_destroy.wav"),
+        wall_bounce: asset_server.load("sounds/wall_bounce.wav"),
+        ball_lost: asset_server.load("sounds/ball_lost.wav"),
+        power_up: asset_server.load("sounds/power_up.wav"),
+        game_over: asset_server.load("sounds/game_over.wav"),
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn play_sound_effects(
+    audio: Res<Audio>,
+    sounds: Res<SoundEffects>,
+    volume: Res<VolumeSettings>,
+    mut paddle_hit_events: EventReader<PaddleHit>,
+    mut brick_hit_events: EventReader<BrickHit>,
+    mut brick_destroyed_events: EventReader<BrickDestroyed>,
+    mut wall_bounce_events: EventReader<WallBounce>,
+    mut ball_lost_events: EventReader<BallLost>,
+    mut power_up_events: EventReader<PowerUpCollected>,
+) {
+    let settings = PlaybackSettings::ONCE.with_volume(volume.sfx_volume());
+    let play = |sound: &Handle<AudioSource>, count: usize| {
+        // Several identical sounds in one frame would only add up to noise.
+        if count > 0 {
+            audio.play_with_settings(sound.clone(), settings);
+        }
+    };
+
+    play(&sounds.paddle_hit, paddle_hit_events.iter().count());
+    play(&sounds.brick_hit, brick_hit_events.iter().count());
+    play(&sounds.brick_destroy, brick_destroyed_events.iter().count());
+    play(&sounds.wall_bounce, wall_bounce_events.iter().count());
+    play(&sounds.ball_lost, ball_lost_events.iter().count());
+    play(&sounds.power_up, power_up_events.iter().count());
+}
+
+/// Only a run lost for lack of lives sounds like one. Clearing the last
+/// level or stopping a replay also ends on the game over screen.
+fn play_game_over_sound(
+    audio: Res<Audio>,
+    sounds: Res<SoundEffects>,
+    volume: Res<VolumeSettings>,
+    run: Res<RunState>,
+) {
+    if run.lives > 0 {
+        return;
+    }
+
+    audio.play_with_settings(
+        sounds.game_over.clone(),
+        PlaybackSettings::ONCE.with_volume(volume.sfx_volume()),
+    );
+}
+
+fn start_level_music(
+    audio: Res<Audio>,
+    audio_sinks: Res<Assets<AudioSink>>,
+    asset_server: Res<AssetServer>,
+    run: Res<RunState>,
+    volume: Res<VolumeSettings>,
+    mut music: ResMut<LevelMusic>,
+) {
+    let Some(level) = Level::get(run.level) else {
+        return;
+    };
+
+    let sink = audio.play_with_settings(
+        asset_server.load(level.music),
+        PlaybackSettings::LOOP.with_volume(volume.music_volume()),
+    );
+
+    music.0 = Some(audio_sinks.get_handle(sink));
+}
+
+/// Starts the music of the level the run moved on to from the beginning.
+fn switch_level_music(
+    audio: Res<Audio>,
+    audio_sinks: Res<Assets<AudioSink>>,
+    asset_server: Res<AssetServer>,
+    mut level_advanced_events: EventReader<LevelAdvanced>,
+    volume: Res<VolumeSettings>,
+    mut music: ResMut<LevelMusic>,
+) {
+    let Some(level) = level_advanced_events
+        .iter()
+        .last()
+        .and_then(|advanced| Level::get(advanced.level))
+    else {
+        return;
+    };
+
+    if let Some(sink) = music.0.take().and_then(|sink| audio_sinks.get(&sink)) {
+        sink.stop();
+    }
+    let sink = audio.play_with_settings(
+        asset_server.load(level.music),
+        PlaybackSettings::LOOP.with_volume(volume.music_volume()),
+    );
+    music.0 = Some(audio_sinks.get_handle(sink));
+}
+
+fn stop_level_music(audio_sinks: Res<Assets<AudioSink>>, mut music: ResMut<LevelMusic>) {
+    if let Some(sink) = music.0.take().and_then(|handle| audio_sinks.get(&handle)) {
+        sink.stop();
+    }
+}
+
+fn apply_music_volume(
+    audio_sinks: Res<Assets<AudioSink>>,
+    music: Res<LevelMusic>,
+    volume: Res<VolumeSettings>,
+) {
+    if !volume.is_changed() {
+        return;
+    }
+
+    if let Some(sink) = music.0.as_ref().and_then(|handle| audio_sinks.get(handle)) {
+        sink.set_volume(volume.music_volume());
+    }
+}
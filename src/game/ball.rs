@@ -1,19 +1,46 @@
 use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
+use rand::Rng;
 
-use crate::game::GameState;
+use crate::game::{
+    bricks_health_check, reset_run, update_player_movement, Arena, BallLost, Brick, BrickHit,
+    Difficulty, GameRng, GameplaySet, LevelAdvanced, PaddleHit, Player, PlayerActions, RunState,
+    Skin, ThemePart, Tuning, RUN_STATES, TICKS_PER_SECOND, WALL_THICKNESS,
+};
 
 pub struct BallPlugin;
 
-const BALL_RADIUS: f32 = 10.;
-const BALL_INIT_SPEED: f32 = 3.;
-
 impl Plugin for BallPlugin {
     fn build(&self, app: &mut App) {
-        app.add_startup_system(initialize).add_systems((
-            update_ball_movement.in_set(OnUpdate(GameState::Playing)),
-            update_ball_direction.in_set(OnUpdate(GameState::Playing)),
-            reset.in_schedule(OnExit(GameState::Playing)),
-        ));
+        app.add_startup_system(initialize)
+            .add_system(apply_tuning.run_if(resource_changed::<Tuning>()))
+            .add_system(
+                launch_ball
+                    .in_set(GameplaySet::Input)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            )
+            .add_system(
+                update_ball_movement
+                    .after(update_player_movement)
+                    .in_set(GameplaySet::Movement)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            )
+            .add_system(
+                update_ball_speed
+                    .before(bricks_health_check)
+                    .in_set(GameplaySet::Cleanup)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            );
+
+        for state in RUN_STATES {
+            app.add_system(reset.after(reset_run).in_schedule(OnEnter(state)));
+        }
+
+        app.add_system(
+            reset
+                .run_if(on_event::<LevelAdvanced>())
+                .in_set(GameplaySet::NextLevel)
+                .in_schedule(CoreSchedule::FixedUpdate),
+        );
     }
 }
 
@@ -21,33 +48,94 @@ impl Plugin for BallPlugin {
 pub struct Ball {
     pub speed: f32,
     pub direction: (i32, i32),
+    pub speed_up: BallSpeedUp,
+    /// Set while the ball sits on the paddle, waiting to be launched.
+    pub caught: Option<CaughtBall>,
+    radius: f32,
+}
+
+/// Where a caught ball sits on the paddle and how long until it launches
+/// by itself.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CaughtBall {
+    /// Distance from the centre of the paddle to the ball.
+    pub offset: f32,
+    pub ticks_left: u32,
+}
+
+/// What made the ball faster since it was served. The ball speed follows
+/// from it and the [`BallSpeedUpTuning`](super::BallSpeedUpTuning).
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+pub struct BallSpeedUp {
+    pub ticks: u64,
+    pub paddle_hits: u32,
+    pub hit_top_wall: bool,
+    pub hit_back_rows: bool,
 }
 
 impl Ball {
-    pub fn get_default_radius(&self) -> f32 {
-        BALL_RADIUS
+    pub fn get_radius(&self) -> f32 {
+        self.radius
     }
 }
 
-fn reset(mut ball_query: Query<(&mut Ball, &mut Transform)>) {
+/// Serves the ball from the middle of the paddle.
+fn reset(
+    mut ball_query: Query<(&mut Ball, &mut Transform), Without<Player>>,
+    player_query: Query<(&Player, &Transform), Without<Ball>>,
+    mut rng: ResMut<GameRng>,
+    run: Res<RunState>,
+    tuning: Res<Tuning>,
+) {
     let (mut ball, mut ball_transform) = ball_query.get_single_mut().unwrap();
+    let (player, player_transform) = player_query.single();
 
-    ball.speed = BALL_INIT_SPEED;
-    ball.direction = (1, 1);
+    ball.speed = tuning.difficulty(run.difficulty).ball_speed;
+    ball.speed_up = BallSpeedUp::default();
+    ball.direction = (if rng.0.gen_bool(0.5) { 1 } else { -1 }, 1);
+    ball.caught = Some(CaughtBall {
+        offset: 0.,
+        ticks_left: tuning.catch_release_ticks,
+    });
 
-    *ball_transform = Transform::from_translation(Vec3::ZERO);
+    ball_transform.translation = ball_on_paddle(&ball, player, player_transform);
+}
+
+/// Where a caught ball sits on the paddle.
+fn ball_on_paddle(ball: &Ball, player: &Player, player_transform: &Transform) -> Vec3 {
+    let offset = ball.caught.map_or(0., |caught| caught.offset);
+    Vec3::new(
+        player_transform.translation.x + offset,
+        player_transform.translation.y + player.get_size().y / 2. + ball.radius,
+        0.,
+    )
+}
+
+/// Resizes the ball when its radius is tuned.
+fn apply_tuning(mut ball_query: Query<(&mut Ball, &mut Transform)>, tuning: Res<Tuning>) {
+    for (mut ball, mut ball_transform) in ball_query.iter_mut() {
+        ball.radius = tuning.ball_radius;
+        ball_transform.scale = Vec3::new(ball.radius, ball.radius, 1.);
+    }
 }
 
 fn initialize(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
+    difficulty: Res<Difficulty>,
+    tuning: Res<Tuning>,
 ) {
+    // A unit circle, scaled to the tuned radius.
     let ball_mesh = commands
         .spawn(MaterialMesh2dBundle {
-            mesh: meshes.add(shape::Circle::new(BALL_RADIUS).into()).into(),
+            mesh: meshes.add(shape::Circle::new(1.).into()).into(),
             material: materials.add(ColorMaterial::from(Color::PURPLE)),
-            transform: Transform::from_translation(Vec3::ZERO),
+            transform: Transform::from_translation(Vec3::ZERO).with_scale(Vec3::new(
+                tuning.ball_radius,
+                tuning.ball_radius,
+                1.,
+            )),
             ..default()
         })
         .id();
@@ -55,44 +143,105 @@ fn initialize(
     commands
         .entity(ball_mesh)
         .insert(Ball {
-            speed: BALL_INIT_SPEED,
+            speed: tuning.difficulty(*difficulty).ball_speed,
             direction: (1, 1),
+            speed_up: BallSpeedUp::default(),
+            caught: None,
+            radius: tuning.ball_radius,
+        })
+        .insert(Skin {
+            part: ThemePart::Ball,
+            size: Vec2::splat(2.),
         })
         .insert(Name::new("BouncingBall"));
 }
 
-fn update_ball_direction(mut ball_query: Query<(&mut Ball, &Transform)>, windows: Query<&Window>) {
-    let (mut ball, transform) = ball_query.single_mut();
-    let window = windows.get_single().unwrap();
+/// Furthest the ball centre can travel from the middle of the arena before
+/// bouncing off the side and top walls.
+pub fn ball_bounds(arena: &Arena, ball: &Ball) -> Vec2 {
+    // Collisions treat the ball as a square as wide as its radius.
+    arena.half_size() - WALL_THICKNESS - ball.radius / 2.
+}
+
+/// Speeds the ball up with time, paddle hits and the first time it reaches
+/// the top wall or the back rows, and slows it back down when it is lost.
+fn update_ball_speed(
+    mut ball_query: Query<&mut Ball>,
+    bricks_query: Query<&Brick>,
+    mut paddle_hit_events: EventReader<PaddleHit>,
+    mut brick_hit_events: EventReader<BrickHit>,
+    mut ball_lost_events: EventReader<BallLost>,
+    run: Res<RunState>,
+    tuning: Res<Tuning>,
+) {
+    let mut ball = ball_query.single_mut();
+    let curve = &tuning.ball_speed_up;
 
-    let limit_x = (window.width() / 2.0) - BALL_RADIUS;
-    let limit_y = (window.height() / 2.0) - BALL_RADIUS;
+    if ball.caught.is_none() {
+        ball.speed_up.ticks += 1;
+    }
+    ball.speed_up.paddle_hits += paddle_hit_events.iter().count() as u32;
+    for hit in brick_hit_events.iter().filter(|hit| hit.by_ball) {
+        if let Ok(brick) = bricks_query.get(hit.brick) {
+            if brick.cell.0 < curve.back_rows {
+                ball.speed_up.hit_back_rows = true;
+            }
+        }
+    }
 
-    if transform.translation.x >= limit_x {
-        ball.direction.0 = -1;
-    } else if transform.translation.x <= -limit_x {
-        ball.direction.0 = 1;
+    if ball_lost_events.iter().count() > 0 {
+        ball.speed_up = BallSpeedUp::default();
     }
 
-    if transform.translation.y >= limit_y {
-        ball.direction.1 = -1;
-    } else if transform.translation.y <= -limit_y {
-        ball.direction.1 = 1;
+    let speed_up = ball.speed_up;
+    let seconds = speed_up.ticks as f32 / TICKS_PER_SECOND as f32;
+    let mut speed = seconds * curve.per_second + speed_up.paddle_hits as f32 * curve.per_paddle_hit;
+    if speed_up.hit_top_wall {
+        speed += curve.top_wall_jump;
     }
+    if speed_up.hit_back_rows {
+        speed += curve.back_rows_jump;
+    }
+
+    let difficulty = tuning.difficulty(run.difficulty);
+    ball.speed = (difficulty.ball_speed + speed).min(difficulty.max_ball_speed);
 }
 
-fn update_ball_movement(mut ball_query: Query<(&Ball, &mut Transform)>) {
+/// Lets go of a caught ball when asked to, or once it was held for too long.
+fn launch_ball(mut ball_query: Query<&mut Ball>, actions: Res<PlayerActions>) {
+    let mut ball = ball_query.single_mut();
+    let Some(caught) = ball.caught.as_mut() else {
+        return;
+    };
+
+    caught.ticks_left = caught.ticks_left.saturating_sub(1);
+    if actions.launch || caught.ticks_left == 0 {
+        ball.caught = None;
+    }
+}
+
+/// Moves the ball, or carries it along with the paddle while it is caught.
+fn update_ball_movement(
+    mut ball_query: Query<(&Ball, &mut Transform), Without<Player>>,
+    player_query: Query<(&Player, &Transform), Without<Ball>>,
+) {
     let (ball, mut transform) = ball_query.single_mut();
 
+    if ball.caught.is_some() {
+        let (player, player_transform) = player_query.single();
+        transform.translation = ball_on_paddle(ball, player, player_transform);
+        return;
+    }
+
     match ball.direction.0 {
-        1 => transform.translation.x = transform.translation.x + ball.speed,
-        -1 => transform.translation.x = transform.translation.x - ball.speed,
-        _ => ()
+        1 => transform.translation.x += ball.speed,
+        -1 => transform.translation.x -= ball.speed,
+        _ => (),
     }
 
     match ball.direction.1 {
-        1 => transform.translation.y = transform.translation.y + ball.speed,
-        -1 => transform.translation.y = transform.translation.y - ball.speed,
-        _ => ()
+        1 => transform.translation.y += ball.speed,
+        -1 => transform.translation.y -= ball.speed,
+        _ => (),
     }
 }
@@ -0,0 +1,476 @@
+//! Boss stages.
+//!
+//! A level with a [`BossSpec`] brings in a boss made of several boxes. Every
+//! box is a [`Collider`], so the ball bounces off the boss through the same
+//! collision path as bricks and walls, each hit taking away some of the
+//! boss's health. The boss fires projectiles in turn from its list of
+//! attacks, costing a life when one hits the paddle. Once out of health it
+//! blinks for a moment and goes away, clearing the level.
+
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::{
+    prelude::*,
+    sprite::{collide_aabb::collide, MaterialMesh2dBundle, Mesh2dHandle},
+};
+
+use super::{
+    bricks_health_check, reset_run, Arena, BossDefeated, BossHit, Collider, ColliderGrid,
+    GameplaySet, Level, LevelAdvanced, LevelCleared, LivesChanged, Player, RunState, Tuning,
+    RUN_STATES,
+};
+
+const BOSS_COLOR: Color = Color::rgb(0.75, 0.55, 0.35);
+const PROJECTILE_SIZE: Vec2 = Vec2::new(10., 10.);
+const PROJECTILE_COLOR: Color = Color::rgb(1., 0.85, 0.3);
+const HEALTH_BAR_SIZE: Vec2 = Vec2::new(240., 8.);
+const HEALTH_BAR_COLOR: Color = Color::rgb(0.9, 0.15, 0.15);
+const HEALTH_BAR_BACKGROUND_COLOR: Color = Color::DARK_GRAY;
+/// Gap between the top of the boss and its health bar.
+const HEALTH_BAR_GAP: f32 = 12.;
+/// Ticks the boss blinks for once out of health, before the level is
+/// cleared.
+const DEFEAT_TICKS: u32 = 120;
+const DEFEAT_BLINK_TICKS: u32 = 8;
+
+pub struct BossPlugin;
+
+impl Plugin for BossPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(load_boss_assets)
+            .add_system(
+                fire_boss_attacks
+                    .in_set(GameplaySet::Input)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            )
+            .add_system(
+                update_projectile_movement
+                    .in_set(GameplaySet::Movement)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            )
+            .add_system(
+                projectile_collision
+                    .in_set(GameplaySet::Collision)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            )
+            .add_systems(
+                (damage_boss, play_boss_defeat, update_health_bar)
+                    .chain()
+                    .before(bricks_health_check)
+                    .in_set(GameplaySet::Cleanup)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            )
+            .add_system(
+                reset
+                    .run_if(on_event::<LevelAdvanced>())
+                    .in_set(GameplaySet::NextLevel)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            )
+            .add_system(
+                start_next_level
+                    .after(reset)
+                    .run_if(on_event::<LevelAdvanced>())
+                    .in_set(GameplaySet::NextLevel)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            );
+
+        for state in RUN_STATES {
+            app.add_system(
+                initialize
+                    .after(reset_run)
+                    .in_schedule(OnEnter(state.clone())),
+            )
+            .add_system(reset.in_schedule(OnExit(state)));
+        }
+    }
+}
+
+/// The boss guarding a level.
+#[derive(Clone, Copy, Debug)]
+pub struct BossSpec {
+    pub name: &'static str,
+    pub health: f32,
+    /// Centre of the boss, from the centre of the arena.
+    pub position: Vec2,
+    /// Centre and size of every box the boss is made of, from its centre.
+    pub parts: &'static [(Vec2, Vec2)],
+    /// Attacks fired in turn, one every `boss_attack_interval_ticks`.
+    pub attacks: &'static [BossAttack],
+}
+
+/// Projectiles fired by the boss from the bottom of its lowest box.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BossAttack {
+    /// One projectile straight down.
+    Shot,
+    /// `count` projectiles fanned out over `angle` radians.
+    Spread { count: u32, angle: f32 },
+    /// One projectile aimed at the paddle.
+    Aimed,
+}
+
+#[derive(Component)]
+pub struct Boss {
+    pub health: f32,
+    pub max_health: f32,
+    /// Ticks left blinking once out of health.
+    pub defeat_ticks_left: Option<u32>,
+}
+
+impl Boss {
+    pub fn is_defeated(&self) -> bool {
+        self.health <= 0.
+    }
+}
+
+/// One of the boxes a boss is made of.
+#[derive(Component)]
+pub struct BossPart {
+    pub boss: Entity,
+}
+
+#[derive(Component)]
+pub struct BossProjectile {
+    /// Distance travelled every tick.
+    pub velocity: Vec2,
+}
+
+#[derive(Component)]
+struct BossHealthBar;
+
+/// The part of the health bar that shrinks as the boss loses health.
+#[derive(Component)]
+struct BossHealthFill;
+
+#[derive(Resource)]
+struct BossAssets {
+    mesh: Mesh2dHandle,
+    material: Handle<ColorMaterial>,
+    projectile_material: Handle<ColorMaterial>,
+    health_bar_material: Handle<ColorMaterial>,
+    health_bar_background_material: Handle<ColorMaterial>,
+}
+
+fn load_boss_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    commands.insert_resource(BossAssets {
+        mesh: meshes.add(shape::Quad::new(Vec2::ONE).into()).into(),
+        material: materials.add(ColorMaterial::from(BOSS_COLOR)),
+        projectile_material: materials.add(ColorMaterial::from(PROJECTILE_COLOR)),
+        health_bar_material: materials.add(ColorMaterial::from(HEALTH_BAR_COLOR)),
+        health_bar_background_material: materials
+            .add(ColorMaterial::from(HEALTH_BAR_BACKGROUND_COLOR)),
+    });
+}
+
+fn initialize(mut commands: Commands, assets: Res<BossAssets>, run: Res<RunState>) {
+    if let Some(level) = Level::get(run.level) {
+        spawn_boss(&mut commands, &assets, level);
+    }
+}
+
+/// Brings in the boss of the level the run moved on to, if it has one.
+fn start_next_level(mut commands: Commands, assets: Res<BossAssets>, run: Res<RunState>) {
+    if let Some(level) = Level::get(run.level) {
+        spawn_boss(&mut commands, &assets, level);
+    }
+}
+
+/// Spawns the boss with its parts and health bar. Parts and bar are not
+/// children of the boss, colliders are looked up by their own translation.
+fn spawn_boss(commands: &mut Commands, assets: &BossAssets, level: &Level) {
+    let Some(spec) = level.boss else {
+        return;
+    };
+
+    let boss = commands
+        .spawn((
+            SpatialBundle::from_transform(Transform::from_translation(spec.position.extend(0.))),
+            Boss {
+                health: spec.health,
+                max_health: spec.health,
+                defeat_ticks_left: None,
+            },
+            Name::new(format!("Boss-{}", spec.name)),
+        ))
+        .id();
+
+    for (offset, size) in spec.parts {
+        commands.spawn((
+            MaterialMesh2dBundle {
+                mesh: assets.mesh.clone(),
+                material: assets.material.clone(),
+                transform: Transform::from_translation((spec.position + *offset).extend(0.))
+                    .with_scale(size.extend(1.)),
+                ..default()
+            },
+            BossPart { boss },
+            Collider { size: *size },
+            Name::new("BossPart"),
+        ));
+    }
+
+    let top = spec
+        .parts
+        .iter()
+        .map(|(offset, size)| offset.y + size.y / 2.)
+        .fold(f32::MIN, f32::max);
+    let bar_position = spec.position + Vec2::new(0., top + HEALTH_BAR_GAP);
+
+    commands.spawn((
+        MaterialMesh2dBundle {
+            mesh: assets.mesh.clone(),
+            material: assets.health_bar_background_material.clone(),
+            transform: Transform::from_translation(bar_position.extend(0.))
+                .with_scale(HEALTH_BAR_SIZE.extend(1.)),
+            ..default()
+        },
+        BossHealthBar,
+        Name::new("BossHealthBarBackground"),
+    ));
+    commands.spawn((
+        MaterialMesh2dBundle {
+            mesh: assets.mesh.clone(),
+            material: assets.health_bar_material.clone(),
+            transform: Transform::from_translation(bar_position.extend(0.1))
+                .with_scale(HEALTH_BAR_SIZE.extend(1.)),
+            ..default()
+        },
+        BossHealthBar,
+        BossHealthFill,
+        Name::new("BossHealthBar"),
+    ));
+}
+
+/// Fires the next attack of the level's boss every
+/// `boss_attack_interval_ticks`, until it is defeated.
+fn fire_boss_attacks(
+    mut commands: Commands,
+    boss_query: Query<(&Boss, &Transform)>,
+    player_query: Query<&Transform, With<Player>>,
+    run: Res<RunState>,
+    assets: Res<BossAssets>,
+    tuning: Res<Tuning>,
+) {
+    let Ok((boss, boss_transform)) = boss_query.get_single() else {
+        return;
+    };
+    let Some(spec) = Level::get(run.level).and_then(|level| level.boss) else {
+        return;
+    };
+
+    let interval = tuning.boss_attack_interval_ticks.max(1) as u64;
+    if boss.is_defeated()
+        || spec.attacks.is_empty()
+        || run.level_ticks == 0
+        || !run.level_ticks.is_multiple_of(interval)
+    {
+        return;
+    }
+
+    let bottom = spec
+        .parts
+        .iter()
+        .map(|(offset, size)| offset.y - size.y / 2.)
+        .fold(f32::MAX, f32::min);
+    let muzzle = boss_transform.translation.truncate() + Vec2::new(0., bottom);
+    let down = -FRAC_PI_2;
+
+    let attack = spec.attacks[(run.level_ticks / interval - 1) as usize % spec.attacks.len()];
+    let angles: Vec<f32> = match attack {
+        BossAttack::Shot => vec![down],
+        BossAttack::Spread { count, angle } if count > 1 => {
+            let step = angle / (count - 1) as f32;
+            (0..count)
+                .map(|index| down - angle / 2. + step * index as f32)
+                .collect()
+        }
+        BossAttack::Spread { .. } => vec![down],
+        BossAttack::Aimed => {
+            let to_paddle = player_query.single().translation.truncate() - muzzle;
+            vec![to_paddle.y.atan2(to_paddle.x)]
+        }
+    };
+
+    for angle in angles {
+        commands.spawn((
+            MaterialMesh2dBundle {
+                mesh: assets.mesh.clone(),
+                material: assets.projectile_material.clone(),
+                transform: Transform::from_translation(muzzle.extend(0.))
+                    .with_scale(PROJECTILE_SIZE.extend(1.)),
+                ..default()
+            },
+            BossProjectile {
+                velocity: Vec2::from_angle(angle) * tuning.boss_projectile_speed,
+            },
+            Name::new("BossProjectile"),
+        ));
+    }
+}
+
+fn update_projectile_movement(mut projectiles_query: Query<(&BossProjectile, &mut Transform)>) {
+    for (projectile, mut transform) in projectiles_query.iter_mut() {
+        transform.translation += projectile.velocity.extend(0.);
+    }
+}
+
+/// Takes a life for every projectile hitting the paddle, and drops the ones
+/// that left the arena.
+fn projectile_collision(
+    mut commands: Commands,
+    projectiles_query: Query<(Entity, &Transform), With<BossProjectile>>,
+    player_query: Query<(&Player, &Transform)>,
+    mut run: ResMut<RunState>,
+    mut lives_changed_event: EventWriter<LivesChanged>,
+    arena: Res<Arena>,
+) {
+    let (player, player_transform) = player_query.single();
+    let half = arena.half_size();
+
+    for (entity, transform) in projectiles_query.iter() {
+        let hit_paddle = collide(
+            transform.translation,
+            PROJECTILE_SIZE,
+            player_transform.translation,
+            player.get_size(),
+        )
+        .is_some();
+
+        if hit_paddle {
+            commands.entity(entity).despawn();
+
+            // The ball is still in play, only the life is lost.
+            run.lives -= 1;
+            lives_changed_event.send(LivesChanged {
+                delta: -1,
+                total: run.lives,
+            });
+        } else if transform.translation.y.abs() > half.y || transform.translation.x.abs() > half.x {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// Takes the damage of this tick's hits off the boss, and starts its defeat
+/// once it runs out of health.
+fn damage_boss(
+    mut commands: Commands,
+    mut boss_query: Query<(Entity, &mut Boss, &Transform)>,
+    parts_query: Query<(Entity, &BossPart)>,
+    projectiles_query: Query<Entity, With<BossProjectile>>,
+    mut boss_hit_events: EventReader<BossHit>,
+    mut boss_defeated_event: EventWriter<BossDefeated>,
+    mut collider_grid: ResMut<ColliderGrid>,
+) {
+    for hit in boss_hit_events.iter() {
+        if let Ok((_, mut boss, _)) = boss_query.get_mut(hit.boss) {
+            boss.health -= hit.damage;
+        }
+    }
+
+    for (boss_entity, mut boss, transform) in boss_query.iter_mut() {
+        if !boss.is_defeated() || boss.defeat_ticks_left.is_some() {
+            continue;
+        }
+
+        info!("Boss defeated");
+        boss.defeat_ticks_left = Some(DEFEAT_TICKS);
+        boss_defeated_event.send(BossDefeated {
+            position: transform.translation.truncate(),
+        });
+
+        // The ball flies through what is left of the boss.
+        for (entity, part) in parts_query.iter() {
+            if part.boss == boss_entity {
+                commands.entity(entity).remove::<Collider>();
+                collider_grid.remove(entity);
+            }
+        }
+        for entity in projectiles_query.iter() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// Blinks the defeated boss, then takes it away and clears the level.
+fn play_boss_defeat(
+    mut commands: Commands,
+    mut boss_query: Query<(Entity, &mut Boss)>,
+    mut parts_query: Query<(Entity, &BossPart, &mut Visibility)>,
+    bars_query: Query<Entity, With<BossHealthBar>>,
+    mut level_cleared_event: EventWriter<LevelCleared>,
+) {
+    for (boss_entity, mut boss) in boss_query.iter_mut() {
+        let Some(ticks_left) = boss.defeat_ticks_left.as_mut() else {
+            continue;
+        };
+        *ticks_left = ticks_left.saturating_sub(1);
+        let ticks_left = *ticks_left;
+
+        let parts = parts_query
+            .iter_mut()
+            .filter(|(_, part, _)| part.boss == boss_entity);
+
+        if ticks_left > 0 {
+            let shown = (ticks_left / DEFEAT_BLINK_TICKS).is_multiple_of(2);
+            for (_, _, mut visibility) in parts {
+                *visibility = if shown {
+                    Visibility::Inherited
+                } else {
+                    Visibility::Hidden
+                };
+            }
+            continue;
+        }
+
+        for (entity, _, _) in parts {
+            commands.entity(entity).despawn();
+        }
+        for entity in bars_query.iter() {
+            commands.entity(entity).despawn();
+        }
+        commands.entity(boss_entity).despawn();
+        level_cleared_event.send(LevelCleared);
+    }
+}
+
+/// Shrinks the health bar from the right as the boss loses health.
+fn update_health_bar(
+    boss_query: Query<&Boss, Changed<Boss>>,
+    mut fills_query: Query<&mut Transform, With<BossHealthFill>>,
+) {
+    let Ok(boss) = boss_query.get_single() else {
+        return;
+    };
+
+    let fraction = (boss.health / boss.max_health).clamp(0., 1.);
+    for mut transform in fills_query.iter_mut() {
+        let left = transform.translation.x - transform.scale.x / 2.;
+        transform.scale.x = HEALTH_BAR_SIZE.x * fraction;
+        transform.translation.x = left + transform.scale.x / 2.;
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn reset(
+    mut commands: Commands,
+    entities_query: Query<
+        Entity,
+        Or<(
+            With<Boss>,
+            With<BossPart>,
+            With<BossProjectile>,
+            With<BossHealthBar>,
+        )>,
+    >,
+    mut collider_grid: ResMut<ColliderGrid>,
+) {
+    for entity in entities_query.iter() {
+        commands.entity(entity).despawn();
+        collider_grid.remove(entity);
+    }
+}
@@ -0,0 +1,57 @@
+use bevy::prelude::*;
+
+use super::{AiController, GameRng, GameState, SeedRun};
+
+/// Seconds without input on the title screen before the demo starts.
+const DEMO_IDLE_SECONDS: f32 = 15.;
+
+pub struct DemoPlugin;
+
+impl Plugin for DemoPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(MenuIdleTimer(Timer::from_seconds(
+            DEMO_IDLE_SECONDS,
+            TimerMode::Once,
+        )))
+        .add_system(reset_idle_timer.in_schedule(OnEnter(GameState::MainMenu)))
+        .add_system(start_demo_when_idle.in_set(OnUpdate(GameState::MainMenu)))
+        .add_system(
+            start_demo
+                .in_set(SeedRun)
+                .in_schedule(OnEnter(GameState::Demo)),
+        )
+        .add_system(stop_demo.in_schedule(OnExit(GameState::Demo)));
+    }
+}
+
+#[derive(Resource)]
+struct MenuIdleTimer(Timer);
+
+fn reset_idle_timer(mut idle_timer: ResMut<MenuIdleTimer>) {
+    idle_timer.0.reset();
+}
+
+fn start_demo_when_idle(
+    mut idle_timer: ResMut<MenuIdleTimer>,
+    mut next_state: ResMut<NextState<GameState>>,
+    time: Res<Time>,
+    keyboard_input: Res<Input<KeyCode>>,
+) {
+    if keyboard_input.get_pressed().next().is_some() {
+        idle_timer.0.reset();
+        return;
+    }
+
+    if idle_timer.0.tick(time.delta()).just_finished() {
+        next_state.set(GameState::Demo);
+    }
+}
+
+fn start_demo(mut commands: Commands, mut rng: ResMut<GameRng>) {
+    rng.reseed(rand::random());
+    commands.insert_resource(AiController::new(4, 15., rand::random()));
+}
+
+fn stop_demo(mut commands: Commands) {
+    commands.remove_resource::<AiController>();
+}
@@ -0,0 +1,77 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{GameState, Settings};
+
+pub struct DifficultyPlugin;
+
+impl Plugin for DifficultyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(process_difficulty_input.in_set(OnUpdate(GameState::MainMenu)));
+    }
+}
+
+/// Difficulty of the next run. The run in progress keeps its own in
+/// [`RunState`](super::RunState). What each one changes is in
+/// [`Tuning`](super::Tuning).
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, Default, Eq, PartialEq, Debug, Hash)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    Insane,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Normal,
+        Difficulty::Hard,
+        Difficulty::Insane,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+            Difficulty::Insane => "Insane",
+        }
+    }
+
+    pub fn to_index(self) -> u8 {
+        Self::ALL.iter().position(|&other| other == self).unwrap() as u8
+    }
+
+    pub fn from_index(index: u8) -> Option<Self> {
+        Self::ALL.get(index as usize).copied()
+    }
+
+    fn next(self) -> Self {
+        Self::ALL[(self.to_index() as usize + 1).min(Self::ALL.len() - 1)]
+    }
+
+    fn previous(self) -> Self {
+        Self::ALL[self.to_index().saturating_sub(1) as usize]
+    }
+}
+
+fn process_difficulty_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut difficulty: ResMut<Difficulty>,
+    mut settings: ResMut<Settings>,
+) {
+    let selected = if keyboard_input.just_pressed(KeyCode::Left) {
+        difficulty.previous()
+    } else if keyboard_input.just_pressed(KeyCode::Right) {
+        difficulty.next()
+    } else {
+        return;
+    };
+
+    if selected != *difficulty {
+        *difficulty = selected;
+        settings.gameplay.difficulty = selected;
+    }
+}
@@ -0,0 +1,308 @@
+//! Brick shards, paddle sparks, the ball trail and screen shake.
+//!
+//! Effects are only for show: they run on the frame clock, use their own
+//! randomness and never touch the simulation, so replays stay exact.
+
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::{Ball, BallLost, BrickDestroyed, GameState, PaddleHit, Settings, RUN_STATES};
+
+const SHARDS_PER_BRICK: usize = 12;
+const SHARD_SIZE: f32 = 6.;
+const SHARD_SPEED: f32 = 220.;
+const SHARD_LIFETIME: f32 = 0.6;
+
+const SPARKS_PER_HIT: usize = 8;
+const SPARK_SIZE: f32 = 3.;
+const SPARK_SPEED: f32 = 260.;
+const SPARK_LIFETIME: f32 = 0.25;
+const SPARK_COLOR: Color = Color::rgb(1., 0.95, 0.6);
+
+const TRAIL_INTERVAL: f32 = 1. / 60.;
+const TRAIL_LIFETIME: f32 = 0.2;
+const TRAIL_COLOR: Color = Color::rgba(0.63, 0.13, 0.94, 0.5);
+
+/// Downward pull on shards and sparks, in pixels per second squared.
+const GRAVITY: f32 = 600.;
+
+/// Largest camera offset, in pixels, at full shake.
+const MAX_SHAKE_OFFSET: f32 = 12.;
+/// Shake added when a ball is lost, between `0.` and `1.`.
+const BALL_LOST_SHAKE: f32 = 0.6;
+/// Shake lost every second.
+const SHAKE_DECAY: f32 = 1.5;
+
+/// Drawn above the bricks, ball and paddle.
+const EFFECTS_Z: f32 = 1.;
+/// Just behind the ball, still in front of the camera's near plane.
+const TRAIL_Z: f32 = -0.05;
+
+pub struct EffectsPlugin;
+
+impl Plugin for EffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ScreenShake>()
+            .init_resource::<TrailTimer>()
+            .add_systems((spawn_brick_shards, spawn_paddle_sparks, spawn_ball_trail))
+            .add_system(update_particles)
+            .add_system(shake_on_ball_lost)
+            .add_system(shake_camera.after(shake_on_ball_lost));
+
+        for state in RUN_STATES {
+            app.add_system(clear_effects.in_schedule(OnExit(state)));
+        }
+    }
+}
+
+/// Each effect can be turned off for players sensitive to motion.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(default)]
+pub struct EffectSettings {
+    /// Brick shards and paddle sparks.
+    pub particles: bool,
+    pub ball_trail: bool,
+    pub screen_shake: bool,
+}
+
+impl Default for EffectSettings {
+    fn default() -> Self {
+        Self {
+            particles: true,
+            ball_trail: true,
+            screen_shake: true,
+        }
+    }
+}
+
+/// A short-lived square that drifts, falls if it has `gravity`, and fades
+/// out over its lifetime.
+#[derive(Component)]
+struct Particle {
+    velocity: Vec2,
+    gravity: bool,
+    age: f32,
+    lifetime: f32,
+    color: Color,
+}
+
+#[derive(Resource, Default)]
+struct ScreenShake {
+    trauma: f32,
+}
+
+#[derive(Resource)]
+struct TrailTimer(Timer);
+
+impl Default for TrailTimer {
+    fn default() -> Self {
+        Self(Timer::from_seconds(TRAIL_INTERVAL, TimerMode::Repeating))
+    }
+}
+
+fn spawn_particle(commands: &mut Commands, position: Vec2, size: f32, particle: Particle) {
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: particle.color,
+                custom_size: Some(Vec2::splat(size)),
+                ..default()
+            },
+            transform: Transform::from_translation(position.extend(EFFECTS_Z)),
+            ..default()
+        },
+        particle,
+    ));
+}
+
+/// A random velocity of up to `speed`, mostly pointing along `direction`.
+fn random_velocity(rng: &mut impl Rng, direction: Vec2, spread: f32, speed: f32) -> Vec2 {
+    let angle = direction.y.atan2(direction.x) + rng.gen_range(-spread..=spread);
+    Vec2::new(angle.cos(), angle.sin()) * speed * rng.gen_range(0.4..=1.)
+}
+
+fn spawn_brick_shards(
+    mut commands: Commands,
+    mut brick_destroyed_events: EventReader<BrickDestroyed>,
+    settings: Res<Settings>,
+) {
+    if !settings.effects.particles {
+        brick_destroyed_events.clear();
+        return;
+    }
+
+    let mut rng = rand::thread_rng();
+    for brick in brick_destroyed_events.iter() {
+        for _ in 0..SHARDS_PER_BRICK {
+            spawn_particle(
+                &mut commands,
+                brick.position,
+                SHARD_SIZE,
+                Particle {
+                    velocity: random_velocity(&mut rng, Vec2::Y, std::f32::consts::PI, SHARD_SPEED),
+                    gravity: true,
+                    age: 0.,
+                    lifetime: SHARD_LIFETIME,
+                    color: brick.kind.color(),
+                },
+            );
+        }
+    }
+}
+
+fn spawn_paddle_sparks(
+    mut commands: Commands,
+    mut paddle_hit_events: EventReader<PaddleHit>,
+    ball_query: Query<(&Ball, &Transform)>,
+    settings: Res<Settings>,
+) {
+    if !settings.effects.particles {
+        paddle_hit_events.clear();
+        return;
+    }
+
+    let Ok((ball, ball_transform)) = ball_query.get_single() else {
+        return;
+    };
+    // Sparks fly off where the ball touches the paddle.
+    let position = ball_transform.translation.truncate() - Vec2::Y * ball.get_radius();
+
+    let mut rng = rand::thread_rng();
+    for _ in paddle_hit_events.iter() {
+        for _ in 0..SPARKS_PER_HIT {
+            spawn_particle(
+                &mut commands,
+                position,
+                SPARK_SIZE,
+                Particle {
+                    velocity: random_velocity(&mut rng, Vec2::Y, 1.2, SPARK_SPEED),
+                    gravity: true,
+                    age: 0.,
+                    lifetime: SPARK_LIFETIME,
+                    color: SPARK_COLOR,
+                },
+            );
+        }
+    }
+}
+
+fn spawn_ball_trail(
+    mut commands: Commands,
+    mut timer: ResMut<TrailTimer>,
+    ball_query: Query<(&Ball, &Transform)>,
+    state: Res<State<GameState>>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+) {
+    if !settings.effects.ball_trail
+        || !RUN_STATES.contains(&state.0)
+        || !timer.0.tick(time.delta()).just_finished()
+    {
+        return;
+    }
+
+    let Ok((ball, ball_transform)) = ball_query.get_single() else {
+        return;
+    };
+
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: TRAIL_COLOR,
+                custom_size: Some(Vec2::splat(ball.get_radius() * 1.6)),
+                ..default()
+            },
+            transform: Transform::from_translation(
+                ball_transform.translation.truncate().extend(TRAIL_Z),
+            ),
+            ..default()
+        },
+        Particle {
+            velocity: Vec2::ZERO,
+            gravity: false,
+            age: 0.,
+            lifetime: TRAIL_LIFETIME,
+            color: TRAIL_COLOR,
+        },
+    ));
+}
+
+fn update_particles(
+    mut commands: Commands,
+    mut particles_query: Query<(Entity, &mut Particle, &mut Transform, &mut Sprite)>,
+    time: Res<Time>,
+) {
+    let delta = time.delta_seconds();
+
+    for (entity, mut particle, mut transform, mut sprite) in particles_query.iter_mut() {
+        particle.age += delta;
+        if particle.age >= particle.lifetime {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        if particle.gravity {
+            particle.velocity.y -= GRAVITY * delta;
+        }
+        transform.translation += (particle.velocity * delta).extend(0.);
+
+        let remaining = 1. - particle.age / particle.lifetime;
+        sprite.color = particle.color.with_a(particle.color.a() * remaining);
+        transform.scale = Vec3::splat(remaining.max(0.3));
+    }
+}
+
+fn clear_effects(
+    mut commands: Commands,
+    particles_query: Query<Entity, With<Particle>>,
+    mut shake: ResMut<ScreenShake>,
+) {
+    for entity in particles_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    shake.trauma = 0.;
+}
+
+fn shake_on_ball_lost(
+    mut shake: ResMut<ScreenShake>,
+    mut ball_lost_events: EventReader<BallLost>,
+    settings: Res<Settings>,
+) {
+    if ball_lost_events.iter().count() > 0 && settings.effects.screen_shake {
+        shake.trauma = (shake.trauma + BALL_LOST_SHAKE).min(1.);
+    }
+}
+
+/// Offsets the camera by a random amount that fades with the shake, and
+/// puts it back in place once the shake is over.
+fn shake_camera(
+    mut shake: ResMut<ScreenShake>,
+    mut camera_query: Query<&mut Transform, With<Camera2d>>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+) {
+    if !settings.effects.screen_shake {
+        shake.trauma = 0.;
+    }
+
+    let offset = if shake.trauma > 0. {
+        let mut rng = rand::thread_rng();
+        // Squared so small shakes stay subtle.
+        let amount = shake.trauma * shake.trauma * MAX_SHAKE_OFFSET;
+        shake.trauma = (shake.trauma - SHAKE_DECAY * time.delta_seconds()).max(0.);
+        Vec2::new(
+            rng.gen_range(-amount..=amount),
+            rng.gen_range(-amount..=amount),
+        )
+    } else {
+        Vec2::ZERO
+    };
+
+    for mut transform in camera_query.iter_mut() {
+        if transform.translation.truncate() != offset {
+            transform.translation.x = offset.x;
+            transform.translation.y = offset.y;
+        }
+    }
+}
@@ -0,0 +1,235 @@
+//! Enemies drifting down from the top of the arena.
+//!
+//! Levels list the spawn points enemies come in from, one after the other at
+//! a steady pace of the level clock. Enemies deflect the ball and die when it
+//! hits them or when they run into the paddle, and leave for good once they
+//! drift past the bottom of the arena.
+
+use std::f32::consts::TAU;
+
+use bevy::{
+    prelude::*,
+    sprite::{
+        collide_aabb::{collide, Collision},
+        MaterialMesh2dBundle, Mesh2dHandle,
+    },
+};
+
+use super::{
+    Arena, Ball, EnemyDestroyed, GameplaySet, Level, LevelAdvanced, Player, RunState, Tuning,
+    RUN_STATES, WALL_THICKNESS,
+};
+
+const ENEMY_SIZE: Vec2 = Vec2::new(28., 28.);
+const ENEMY_COLOR: Color = Color::rgb(0.7, 0.3, 1.);
+/// How far weaving enemies swing either side of where they came in.
+const WEAVE_REACH: f32 = 80.;
+const WEAVE_PERIOD_TICKS: u32 = 240;
+
+pub struct EnemyPlugin;
+
+impl Plugin for EnemyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(load_enemy_assets)
+            .add_system(
+                spawn_enemies
+                    .in_set(GameplaySet::Input)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            )
+            .add_system(
+                update_enemy_movement
+                    .in_set(GameplaySet::Movement)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            )
+            .add_system(
+                enemy_collision
+                    .in_set(GameplaySet::Collision)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            )
+            .add_system(
+                reset
+                    .run_if(on_event::<LevelAdvanced>())
+                    .in_set(GameplaySet::NextLevel)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            );
+
+        for state in RUN_STATES {
+            app.add_system(reset.in_schedule(OnExit(state)));
+        }
+    }
+}
+
+/// How an enemy finds its way down the arena.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EnemyBehavior {
+    /// Falls straight down.
+    Fall,
+    /// Weaves from side to side on the way down.
+    Weave,
+    /// Steers towards the paddle on the way down.
+    Chase,
+}
+
+/// A point along the top wall the enemies of a level come in from.
+#[derive(Clone, Copy, Debug)]
+pub struct EnemySpawn {
+    pub x: f32,
+    pub behavior: EnemyBehavior,
+}
+
+#[derive(Component)]
+pub struct Enemy {
+    pub behavior: EnemyBehavior,
+    /// Where the enemy came in, weaving enemies swing around it.
+    pub origin_x: f32,
+    /// Ticks since the enemy came in.
+    pub age: u32,
+}
+
+#[derive(Resource)]
+struct EnemyAssets {
+    mesh: Mesh2dHandle,
+    material: Handle<ColorMaterial>,
+}
+
+fn load_enemy_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    commands.insert_resource(EnemyAssets {
+        mesh: meshes
+            .add(shape::Circle::new(ENEMY_SIZE.x / 2.).into())
+            .into(),
+        material: materials.add(ColorMaterial::from(ENEMY_COLOR)),
+    });
+}
+
+/// Brings in an enemy from the next spawn point of the level every
+/// `enemy_spawn_interval_ticks`, while there is room for one more.
+fn spawn_enemies(
+    mut commands: Commands,
+    enemies_query: Query<(), With<Enemy>>,
+    run: Res<RunState>,
+    arena: Res<Arena>,
+    assets: Res<EnemyAssets>,
+    tuning: Res<Tuning>,
+) {
+    let Some(level) = Level::get(run.level) else {
+        return;
+    };
+
+    let interval = tuning.enemy_spawn_interval_ticks.max(1) as u64;
+    if level.enemy_spawns.is_empty()
+        || run.level_ticks == 0
+        || !run.level_ticks.is_multiple_of(interval)
+        || enemies_query.iter().count() >= tuning.max_enemies as usize
+    {
+        return;
+    }
+
+    let index = (run.level_ticks / interval - 1) as usize % level.enemy_spawns.len();
+    let spawn = level.enemy_spawns[index];
+    let y = arena.half_size().y - WALL_THICKNESS - ENEMY_SIZE.y / 2.;
+
+    commands.spawn((
+        MaterialMesh2dBundle {
+            mesh: assets.mesh.clone(),
+            material: assets.material.clone(),
+            transform: Transform::from_xyz(spawn.x, y, 0.),
+            ..default()
+        },
+        Enemy {
+            behavior: spawn.behavior,
+            origin_x: spawn.x,
+            age: 0,
+        },
+        Name::new(format!("Enemy-{:?}", spawn.behavior)),
+    ));
+}
+
+fn update_enemy_movement(
+    mut enemies_query: Query<(&mut Enemy, &mut Transform), Without<Player>>,
+    player_query: Query<&Transform, With<Player>>,
+    arena: Res<Arena>,
+    tuning: Res<Tuning>,
+) {
+    let paddle_x = player_query.single().translation.x;
+    let limit = arena.half_size().x - WALL_THICKNESS - ENEMY_SIZE.x / 2.;
+
+    for (mut enemy, mut transform) in enemies_query.iter_mut() {
+        enemy.age += 1;
+
+        let x = match enemy.behavior {
+            EnemyBehavior::Fall => transform.translation.x,
+            EnemyBehavior::Weave => {
+                let phase = (enemy.age % WEAVE_PERIOD_TICKS) as f32 / WEAVE_PERIOD_TICKS as f32;
+                enemy.origin_x + WEAVE_REACH * (phase * TAU).sin()
+            }
+            EnemyBehavior::Chase => {
+                let step = (paddle_x - transform.translation.x)
+                    .clamp(-tuning.enemy_speed, tuning.enemy_speed);
+                transform.translation.x + step
+            }
+        };
+
+        transform.translation.x = x.clamp(-limit, limit);
+        transform.translation.y -= tuning.enemy_speed;
+    }
+}
+
+/// Kills the enemies the ball hits, bouncing it off them, and the ones that
+/// run into the paddle. Enemies that drifted past the paddle just leave.
+fn enemy_collision(
+    mut commands: Commands,
+    enemies_query: Query<(Entity, &Transform), With<Enemy>>,
+    mut ball_query: Query<(&mut Ball, &Transform)>,
+    player_query: Query<(&Player, &Transform)>,
+    mut enemy_destroyed_event: EventWriter<EnemyDestroyed>,
+    arena: Res<Arena>,
+) {
+    let (mut ball, ball_transform) = ball_query.single_mut();
+    let (player, player_transform) = player_query.single();
+
+    for (entity, transform) in enemies_query.iter() {
+        let ball_collision = collide(
+            ball_transform.translation,
+            Vec2::splat(ball.get_radius()),
+            transform.translation,
+            ENEMY_SIZE,
+        );
+
+        if let Some(collision) = &ball_collision {
+            match collision {
+                Collision::Left => ball.direction.0 = -1,
+                Collision::Right => ball.direction.0 = 1,
+                Collision::Top => ball.direction.1 = 1,
+                Collision::Bottom => ball.direction.1 = -1,
+                Collision::Inside => (),
+            }
+        }
+
+        let hit_paddle = collide(
+            transform.translation,
+            ENEMY_SIZE,
+            player_transform.translation,
+            player.get_size(),
+        )
+        .is_some();
+
+        if ball_collision.is_some() || hit_paddle {
+            commands.entity(entity).despawn();
+            enemy_destroyed_event.send(EnemyDestroyed {
+                position: transform.translation.truncate(),
+            });
+        } else if transform.translation.y < -arena.half_size().y - ENEMY_SIZE.y {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn reset(mut commands: Commands, enemies_query: Query<Entity, With<Enemy>>) {
+    for entity in enemies_query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
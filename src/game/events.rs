@@ -0,0 +1,83 @@
+//! Gameplay events. They are sent from the fixed timestep simulation, so
+//! anything reacting to the game (HUD, audio, effects, statistics) should
+//! listen to them instead of polling components.
+
+use bevy::prelude::*;
+
+use super::{BrickKind, PowerUpKind};
+
+/// The ball or a laser bolt hit a brick and dealt `damage` to it.
+pub struct BrickHit {
+    pub brick: Entity,
+    pub damage: f32,
+    pub by_ball: bool,
+}
+
+/// A brick ran out of health and was removed.
+pub struct BrickDestroyed {
+    pub kind: BrickKind,
+    pub position: Vec2,
+}
+
+/// The ball fell past the paddle, costing a life.
+pub struct BallLost;
+
+/// The ball bounced off the paddle. `offset` is where it landed, from `-1.`
+/// on the left edge of the paddle to `1.` on the right edge.
+pub struct PaddleHit {
+    pub offset: f32,
+}
+
+/// The ball bounced off a wall of the arena.
+pub struct WallBounce;
+
+/// The ball hit an enemy, or an enemy ran into the paddle, and it died.
+pub struct EnemyDestroyed {
+    pub position: Vec2,
+}
+
+/// The ball hit a part of the boss `boss` and dealt `damage` to it.
+pub struct BossHit {
+    pub boss: Entity,
+    pub damage: f32,
+}
+
+/// A boss ran out of health. The level is cleared once its defeat is over.
+pub struct BossDefeated {
+    pub position: Vec2,
+}
+
+/// The paddle caught a power-up capsule.
+pub struct PowerUpCollected {
+    pub kind: PowerUpKind,
+}
+
+/// The paddle went out through the open warp gate, ending the level.
+pub struct PaddleWarped;
+
+/// Points were scored, or the score was reset when a run started.
+pub struct ScoreChanged {
+    pub delta: i32,
+    pub total: i32,
+}
+
+/// A life was lost, or the lives were reset when a run started.
+pub struct LivesChanged {
+    pub delta: i32,
+    pub total: i32,
+}
+
+/// The combo counter or the score multiplier it drives changed.
+pub struct ComboChanged {
+    pub combo: u32,
+    pub multiplier: i32,
+}
+
+/// The last brick of the level was destroyed.
+pub struct LevelCleared;
+
+/// The run moved on to the level with id `level`, after the previous one was
+/// cleared or warped out of.
+pub struct LevelAdvanced {
+    pub level: u32,
+}
@@ -1,35 +1,159 @@
-use std::path::Prefix;
-
 use super::{
-    Ball, BallPlugin, Brick, GameHudPlugin, GameOverPlugin, Player, PlayerPlugin, WallPlugin,
+    award_warp_bonus, ActivePowerUp, AiPlugin, ArenaPlugin, ArenaWall, Ball, BallLost, BallPlugin,
+    BossDefeated, BossHit, BossPart, BossPlugin, Brick, BrickDestroyed, BrickHit, CaughtBall,
+    Collider, ColliderGrid, ColliderGridPlugin, ComboChanged, CurrentLevel, DemoPlugin, Difficulty,
+    DifficultyPlugin, EffectsPlugin, EnemyDestroyed, EnemyPlugin, GameAudioPlugin, GameHudPlugin,
+    GameOverPlugin, HighScoresPlugin, LaserPlugin, Level, LevelAdvanced, LevelCleared,
+    LivesChanged, MainMenuPlugin, MovingBrick, PaddleHit, PaddleWarped, Player, PlayerPlugin,
+    PowerUpCollected, PowerUpKind, PowerUpPlugin, ReplayPlugin, ResumeRun, SavePlugin,
+    ScoreChanged, ScoringPlugin, Settings, SettingsPlugin, ThemePlugin, Tuning, TuningPlugin,
+    WallBounce, WallPlugin, WallSide,
 };
 use bevy::{
+    ecs::schedule::ExecutorKind,
     prelude::*,
     sprite::{
         collide_aabb::{collide, Collision},
         MaterialMesh2dBundle,
     },
 };
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
 
 #[derive(Clone, Eq, PartialEq, Debug, Hash, States, Default)]
 pub enum GameState {
     #[default]
+    MainMenu,
     Playing,
+    Replaying,
+    Demo,
     GameOver,
     PauseMenu,
 }
 
-#[derive(Default)]
-pub struct UpdateScore;
+/// States in which a run is being simulated: live, from a replay or by the
+/// attract mode AI.
+pub const RUN_STATES: [GameState; 3] = [GameState::Playing, GameState::Replaying, GameState::Demo];
+
+/// Simulation rate of the fixed timestep gameplay schedule.
+pub const TICKS_PER_SECOND: u32 = 60;
+
+/// Stages of a gameplay tick, run in order in [`CoreSchedule::FixedUpdate`].
+#[derive(SystemSet, Clone, Copy, Eq, PartialEq, Debug, Hash)]
+pub enum GameplaySet {
+    /// Fill [`PlayerActions`](super::PlayerActions) from the keyboard or a replay.
+    ReadActions,
+    Input,
+    Movement,
+    Collision,
+    Cleanup,
+    /// Set up the next level, only on ticks sending
+    /// [`LevelAdvanced`](super::LevelAdvanced).
+    NextLevel,
+}
+
+/// Systems seeding [`GameRng`] and picking the [`CurrentLevel`] and
+/// [`Difficulty`] when a run starts. Anything relying on them on run start
+/// has to run after it.
+#[derive(SystemSet, Clone, Copy, Eq, PartialEq, Debug, Hash)]
+pub struct SeedRun;
+
+/// The only source of randomness for gameplay, reseeded at the start of
+/// every run so replays can reproduce it.
+#[derive(Resource)]
+pub struct GameRng(pub ChaCha8Rng);
+
+impl GameRng {
+    pub fn reseed(&mut self, seed: u64) {
+        self.0 = ChaCha8Rng::seed_from_u64(seed);
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self(ChaCha8Rng::seed_from_u64(0))
+    }
+}
+
+/// Size of the play field, centred on the origin. The window is created
+/// with the same size.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct Arena {
+    pub size: Vec2,
+}
+
+pub const ARENA_SIZE: Vec2 = Vec2::new(1280., 720.);
+
+impl Arena {
+    pub fn half_size(&self) -> Vec2 {
+        self.size / 2.
+    }
+}
+
+impl Default for Arena {
+    fn default() -> Self {
+        Self { size: ARENA_SIZE }
+    }
+}
+
+/// Everything about the run in progress. Replaced as a whole when a run
+/// starts, and kept afterwards for the game over screen.
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct RunState {
+    pub score: i32,
+    pub lives: i32,
+    /// Id of the level being played.
+    pub level: u32,
+    pub difficulty: Difficulty,
+    /// Bricks hit since the ball last touched the paddle.
+    pub combo: u32,
+    /// Factor applied to every brick destroyed, grows with the combo.
+    pub multiplier: i32,
+    /// Gameplay ticks simulated since the run started.
+    pub elapsed_ticks: u64,
+    /// Gameplay ticks simulated since the current level started.
+    pub level_ticks: u64,
+    pub bricks_broken: u32,
+    /// Whether the last level was finished, by destroying every brick or
+    /// warping out of it.
+    pub cleared: bool,
+}
+
+impl RunState {
+    pub fn new(level: u32, difficulty: Difficulty, lives: i32) -> Self {
+        Self {
+            score: 0,
+            lives,
+            level,
+            difficulty,
+            combo: 0,
+            multiplier: 1,
+            elapsed_ticks: 0,
+            level_ticks: 0,
+            bricks_broken: 0,
+            cleared: false,
+        }
+    }
 
-#[derive(Default)]
-pub struct UpdateHealth;
+    pub fn elapsed_secs(&self) -> f32 {
+        self.elapsed_ticks as f32 / TICKS_PER_SECOND as f32
+    }
 
-#[derive(Component, Default)]
-pub struct Score(pub i32);
+    pub fn level_elapsed_secs(&self) -> f32 {
+        self.level_ticks as f32 / TICKS_PER_SECOND as f32
+    }
+}
 
-#[derive(Component, Default)]
-pub struct Health(pub i32);
+impl Default for RunState {
+    fn default() -> Self {
+        let difficulty = Difficulty::default();
+        Self::new(
+            CurrentLevel::default().0,
+            difficulty,
+            Tuning::default().difficulty(difficulty).lives,
+        )
+    }
+}
 
 #[derive(Component)]
 pub struct Ground {
@@ -37,48 +161,146 @@ pub struct Ground {
     pub size: Vec2,
 }
 
+/// The whole game: [`GameplayPlugin`] plus everything the player sees and
+/// interacts with outside of the simulation.
 pub struct GamePlugin;
 
 impl Plugin for GamePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(GameplayPlugin)
+            .add_startup_system(spawn_camera)
+            .add_plugin(GameHudPlugin)
+            .add_plugin(GameOverPlugin)
+            .add_plugin(MainMenuPlugin)
+            .add_plugin(ReplayPlugin)
+            .add_plugin(DemoPlugin)
+            .add_plugin(GameAudioPlugin)
+            .add_plugin(SavePlugin)
+            .add_plugin(HighScoresPlugin)
+            .add_plugin(SettingsPlugin)
+            .add_plugin(DifficultyPlugin)
+            .add_plugin(EffectsPlugin)
+            .add_plugin(ThemePlugin)
+            .add_system(process_global_input);
+    }
+}
+
+/// The simulation only. Needs no window or renderer, which lets the
+/// simulator binary run games headless.
+pub struct GameplayPlugin;
+
+impl Plugin for GameplayPlugin {
     fn build(&self, app: &mut App) {
         app.add_state::<GameState>()
-            .add_event::<UpdateScore>()
-            .add_event::<UpdateHealth>()
+            .insert_resource(FixedTime::new_from_secs(1. / TICKS_PER_SECOND as f32))
+            .init_resource::<GameRng>()
+            .init_resource::<CurrentLevel>()
+            .init_resource::<Difficulty>()
+            .init_resource::<RunState>()
+            .init_resource::<Arena>()
+            .init_resource::<Settings>()
+            .edit_schedule(CoreSchedule::FixedUpdate, |schedule| {
+                // Replays rely on every tick running its systems in the same order.
+                schedule
+                    .set_executor_kind(ExecutorKind::SingleThreaded)
+                    .configure_sets(
+                        (
+                            GameplaySet::ReadActions,
+                            GameplaySet::Input,
+                            GameplaySet::Movement,
+                            GameplaySet::Collision,
+                            GameplaySet::Cleanup,
+                            GameplaySet::NextLevel,
+                        )
+                            .chain(),
+                    );
+
+                for set in [
+                    GameplaySet::ReadActions,
+                    GameplaySet::Input,
+                    GameplaySet::Movement,
+                    GameplaySet::Collision,
+                    GameplaySet::Cleanup,
+                    GameplaySet::NextLevel,
+                ] {
+                    schedule.configure_set(set.run_if(is_simulating));
+                }
+            })
+            .add_event::<BrickHit>()
+            .add_event::<BrickDestroyed>()
+            .add_event::<BallLost>()
+            .add_event::<PaddleHit>()
+            .add_event::<WallBounce>()
+            .add_event::<PowerUpCollected>()
+            .add_event::<EnemyDestroyed>()
+            .add_event::<BossHit>()
+            .add_event::<BossDefeated>()
+            .add_event::<PaddleWarped>()
+            .add_event::<LevelCleared>()
+            .add_event::<LevelAdvanced>()
+            .add_event::<ScoreChanged>()
+            .add_event::<ComboChanged>()
+            .add_event::<LivesChanged>()
             .add_startup_system(initialize)
+            .add_plugin(TuningPlugin)
             .add_plugin(BallPlugin)
             .add_plugin(PlayerPlugin)
-            .add_plugin(GameHudPlugin)
-            .add_plugin(GameOverPlugin)
             .add_plugin(WallPlugin)
-            .add_system(process_global_input)
-            .add_system(game_over.in_set(OnUpdate(GameState::Playing)))
-            .add_system(ball_hit_bottom.in_set(OnUpdate(GameState::Playing)))
-            .add_system(reset_score.in_schedule(OnEnter(GameState::Playing)))
-            .add_system(reset_health.in_schedule(OnExit(GameState::Playing)))
-            .add_system(ball_block_collision.in_set(OnUpdate(GameState::Playing)))
-            .add_system(player_ball_collision.in_set(OnUpdate(GameState::Playing)));
+            .add_plugin(ArenaPlugin)
+            .add_plugin(ColliderGridPlugin)
+            .add_plugin(AiPlugin)
+            .add_plugin(ScoringPlugin)
+            .add_plugin(PowerUpPlugin)
+            .add_plugin(LaserPlugin)
+            .add_plugin(EnemyPlugin)
+            .add_plugin(BossPlugin)
+            .add_systems(
+                (
+                    ball_hit_bottom,
+                    ball_collider_collision,
+                    player_ball_collision,
+                )
+                    .chain()
+                    .in_set(GameplaySet::Collision)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            )
+            .add_system(
+                tick_run_clock
+                    .in_set(GameplaySet::Cleanup)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            )
+            .add_systems(
+                (advance_level, game_over)
+                    .chain()
+                    .after(award_warp_bonus)
+                    .in_set(GameplaySet::Cleanup)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            );
+
+        for state in RUN_STATES {
+            app.add_system(reset_run.after(SeedRun).in_schedule(OnEnter(state)));
+        }
     }
 }
 
-const BALL_INC_SPEED_FACTOR: f32 = 0.2;
-const SCORE_POINT_FACTOR: i32 = 10;
+/// Whether gameplay ticks should run. Stops as soon as a state change is
+/// queued so no extra ticks are simulated before it is applied.
+pub fn is_simulating(state: Res<State<GameState>>, next_state: Res<NextState<GameState>>) -> bool {
+    next_state.0.is_none() && RUN_STATES.contains(&state.0)
+}
+
+fn spawn_camera(mut commands: Commands) {
+    commands.spawn(Camera2dBundle::default());
+}
 
 fn initialize(
     mut commands: Commands,
-    mut update_score_event: EventWriter<UpdateScore>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
-    window_query: Query<&Window>,
+    arena: Res<Arena>,
 ) {
-    commands.spawn(Camera2dBundle::default());
-    commands.spawn(Score(0));
-    commands.spawn(Health(100));
-
-    let window = window_query.get_single().unwrap();
-    let window_width = window.width();
-
     let ground_rect_size = Vec2 {
-        x: window_width,
+        x: arena.size.x,
         y: 25.,
     };
 
@@ -95,30 +317,46 @@ fn initialize(
         entity: ground_rect,
         size: ground_rect_size,
     });
-
-    update_score_event.send_default();
 }
 
-fn reset_score(
-    mut score_query: Query<&mut Score>,
-    mut update_score_event: EventWriter<UpdateScore>,
+/// Starts a new [`RunState`] on the level and difficulty picked for the run,
+/// or those of the saved run being resumed. Anything setting the arena up
+/// for the run reads them from there, after this.
+#[allow(clippy::too_many_arguments)]
+pub fn reset_run(
+    mut run: ResMut<RunState>,
+    current_level: Res<CurrentLevel>,
+    difficulty: Res<Difficulty>,
+    resume: Option<Res<ResumeRun>>,
+    tuning: Res<Tuning>,
+    mut score_changed_event: EventWriter<ScoreChanged>,
+    mut lives_changed_event: EventWriter<LivesChanged>,
+    mut combo_changed_event: EventWriter<ComboChanged>,
 ) {
-    let mut score = score_query.get_single_mut().unwrap();
-
-    score.0 = 0;
+    let (level, difficulty) = match resume {
+        Some(resume) => (resume.0.run.level, resume.0.run.difficulty),
+        None => (current_level.0, *difficulty),
+    };
+    let lives = tuning.difficulty(difficulty).lives;
+    let previous = std::mem::replace(run.as_mut(), RunState::new(level, difficulty, lives));
 
-    update_score_event.send_default();
+    score_changed_event.send(ScoreChanged {
+        delta: run.score - previous.score,
+        total: run.score,
+    });
+    lives_changed_event.send(LivesChanged {
+        delta: run.lives - previous.lives,
+        total: run.lives,
+    });
+    combo_changed_event.send(ComboChanged {
+        combo: run.combo,
+        multiplier: run.multiplier,
+    });
 }
 
-fn reset_health(
-    mut health_query: Query<&mut Health>,
-    mut update_health_event: EventWriter<UpdateHealth>,
-) {
-    let mut health = health_query.get_single_mut().unwrap();
-
-    health.0 = 100;
-
-    update_health_event.send_default();
+fn tick_run_clock(mut run: ResMut<RunState>) {
+    run.elapsed_ticks += 1;
+    run.level_ticks += 1;
 }
 
 fn process_global_input(
@@ -127,29 +365,77 @@ fn process_global_input(
     keyboard_input: Res<Input<KeyCode>>,
 ) {
     match current_state.0 {
+        GameState::MainMenu => {
+            if keyboard_input.just_pressed(KeyCode::Return) {
+                next_state.set(GameState::Playing);
+            }
+        }
         GameState::GameOver => {
-            if keyboard_input.pressed(KeyCode::Escape) {
+            if keyboard_input.just_pressed(KeyCode::Escape) {
                 next_state.set(GameState::Playing);
             }
         }
+        GameState::Replaying => {
+            if keyboard_input.just_pressed(KeyCode::Escape) {
+                next_state.set(GameState::GameOver);
+            }
+        }
+        GameState::Demo => {
+            if keyboard_input.get_just_pressed().next().is_some() {
+                next_state.set(GameState::MainMenu);
+            }
+        }
         GameState::Playing => {}
         GameState::PauseMenu => {}
     }
 }
 
-fn game_over(mut state: ResMut<NextState<GameState>>, health_query: Query<&Health>) {
-    let health = health_query.get_single().unwrap();
+/// Moves the run on to the next level once the current one is cleared or
+/// warped out of, or finishes the run if it was the last one.
+fn advance_level(
+    mut run: ResMut<RunState>,
+    mut level_cleared_events: EventReader<LevelCleared>,
+    mut paddle_warped_events: EventReader<PaddleWarped>,
+    mut level_advanced_event: EventWriter<LevelAdvanced>,
+) {
+    let cleared = level_cleared_events.iter().count() > 0;
+    let warped = paddle_warped_events.iter().count() > 0;
+    if !cleared && !warped {
+        return;
+    }
+
+    match Level::get(run.level).and_then(Level::next) {
+        Some(next) => {
+            info!("Moving on to level {} \"{}\"", next.id, next.name);
+            run.level = next.id;
+            run.level_ticks = 0;
+            level_advanced_event.send(LevelAdvanced { level: next.id });
+        }
+        None => run.cleared = true,
+    }
+}
 
-    if health.0 <= 0 {
-        let _ = state.set(GameState::GameOver);
+/// Ends the run once the player is out of lives or the last level is
+/// finished.
+fn game_over(
+    mut state: ResMut<NextState<GameState>>,
+    current_state: Res<State<GameState>>,
+    run: Res<RunState>,
+) {
+    if run.lives <= 0 || run.cleared {
+        match current_state.0 {
+            GameState::Demo => state.set(GameState::MainMenu),
+            _ => state.set(GameState::GameOver),
+        }
     }
 }
 
 fn ball_hit_bottom(
     mut ball_query: Query<(&mut Ball, &Transform)>,
     mut ground_query: Query<(&Ground, &Transform)>,
-    mut health_query: Query<&mut Health>,
-    mut update_health_event: EventWriter<UpdateHealth>,
+    mut run: ResMut<RunState>,
+    mut lives_changed_event: EventWriter<LivesChanged>,
+    mut ball_lost_event: EventWriter<BallLost>,
 ) {
     let (mut ball, ball_transform) = ball_query.get_single_mut().unwrap();
     let (ground, ground_transform) = ground_query.get_single_mut().unwrap();
@@ -157,104 +443,183 @@ fn ball_hit_bottom(
     let collision = collide(
         ball_transform.translation,
         Vec2 {
-            x: ball.get_default_radius(),
-            y: ball.get_default_radius(),
+            x: ball.get_radius(),
+            y: ball.get_radius(),
         },
         ground_transform.translation,
         ground.size,
     );
 
-    let mut health = health_query.get_single_mut().unwrap();
-
-    if collision.is_some() {
-        match collision.unwrap() {
-            Collision::Left => {
-                println!("Left");
-            }
-            Collision::Right => {
-                println!("Right");
-            }
-            Collision::Top => {
-                update_health_event.send_default();
-
-                health.0 -= 10;
+    if collision == Some(Collision::Top) {
+        run.lives -= 1;
 
-                println!("Health: {}", health.0);
+        ball_lost_event.send(BallLost);
+        lives_changed_event.send(LivesChanged {
+            delta: -1,
+            total: run.lives,
+        });
 
-                ball.speed += BALL_INC_SPEED_FACTOR;
-                ball.direction.1 = 1;
-            }
-            Collision::Bottom => {
-                println!("Bottom");
-            }
-            Collision::Inside => {
-                println!("Inside");
-            }
-        }
+        ball.direction.1 = 1;
     }
 }
 
-fn ball_block_collision(
-    mut bricks_query: Query<(&mut Brick, &Transform)>,
-    mut ball_query: Query<(&mut Ball, &Transform)>,
-    mut score_query: Query<&mut Score>,
-    mut update_score_event: EventWriter<UpdateScore>,
+/// Bounces the ball off bricks, walls and bosses, damaging the bricks and
+/// bosses it hits.
+#[allow(clippy::type_complexity)]
+fn ball_collider_collision(
+    mut colliders_query: Query<
+        (
+            &Collider,
+            &Transform,
+            Option<&mut Brick>,
+            Option<&ArenaWall>,
+            Option<&MovingBrick>,
+            Option<&BossPart>,
+        ),
+        Without<Ball>,
+    >,
+    mut ball_query: Query<(&mut Ball, &mut Transform)>,
+    mut brick_hit_event: EventWriter<BrickHit>,
+    mut boss_hit_event: EventWriter<BossHit>,
+    mut wall_bounce_event: EventWriter<WallBounce>,
+    collider_grid: Res<ColliderGrid>,
+    tuning: Res<Tuning>,
 ) {
-    let (mut ball, ball_transform) = ball_query.get_single_mut().unwrap();
-    for (mut brick, brick_transform) in bricks_query.iter_mut() {
+    let (mut ball, mut ball_transform) = ball_query.get_single_mut().unwrap();
+    let nearby_colliders = collider_grid.query(
+        ball_transform.translation.truncate(),
+        Vec2::splat(ball.get_radius() * 2.),
+    );
+
+    for entity in nearby_colliders {
+        let Ok((collider, collider_transform, brick, wall, moving, boss_part)) =
+            colliders_query.get_mut(entity)
+        else {
+            continue;
+        };
+
         let collision = collide(
             ball_transform.translation,
             Vec2 {
-                x: ball.get_default_radius(),
-                y: ball.get_default_radius(),
+                x: ball.get_radius(),
+                y: ball.get_radius(),
             },
-            brick_transform.translation,
-            brick.get_brick_size(),
+            collider_transform.translation,
+            collider.size,
         );
 
-        if collision.is_some() {
-            let mut score = score_query.get_single_mut().unwrap();
-            score.0 += SCORE_POINT_FACTOR;
+        let Some(collision) = collision else {
+            continue;
+        };
+
+        let previous_direction = ball.direction;
+        match collision {
+            Collision::Left => ball.direction.0 = -1,
+            Collision::Right => ball.direction.0 = 1,
+            Collision::Top => ball.direction.1 = 1,
+            Collision::Bottom => ball.direction.1 = -1,
+            Collision::Inside => (),
+        }
+
+        // A brick running into the ball pushes it out of the way, or the ball
+        // would still be inside it on the next tick.
+        if let Some(moving) = moving {
+            let reach = (collider.size + ball.get_radius()) / 2.;
+            let center = collider_transform.translation;
+            match collision {
+                Collision::Left if moving.velocity.x < 0. => {
+                    ball_transform.translation.x = center.x - reach.x;
+                }
+                Collision::Right if moving.velocity.x > 0. => {
+                    ball_transform.translation.x = center.x + reach.x;
+                }
+                Collision::Top if moving.velocity.y > 0. => {
+                    ball_transform.translation.y = center.y + reach.y;
+                }
+                Collision::Bottom if moving.velocity.y < 0. => {
+                    ball_transform.translation.y = center.y - reach.y;
+                }
+                _ => (),
+            }
+        }
 
-            update_score_event.send_default();
+        if let Some(mut brick) = brick {
+            brick.apply_damage(tuning.ball_damage);
+            brick_hit_event.send(BrickHit {
+                brick: brick.entity,
+                damage: tuning.ball_damage,
+                by_ball: true,
+            });
+        }
 
-            brick.apply_damage(100.);
+        if let Some(boss_part) = boss_part {
+            boss_hit_event.send(BossHit {
+                boss: boss_part.boss,
+                damage: tuning.ball_damage,
+            });
+        }
 
-            match collision.unwrap() {
-                Collision::Left => ball.direction.0 = -1,
-                Collision::Right => ball.direction.0 = 1,
-                Collision::Top => ball.direction.1 = 1,
-                Collision::Bottom => ball.direction.1 = -1,
-                Collision::Inside => (),
+        if let Some(wall) = wall {
+            if wall.side == WallSide::Top {
+                ball.speed_up.hit_top_wall = true;
+            }
+            if ball.direction != previous_direction {
+                wall_bounce_event.send(WallBounce);
             }
         }
     }
 }
 
+/// Bounces the ball off the paddle, or catches it with the Catch power-up.
 fn player_ball_collision(
     player_query: Query<(&Player, &Transform)>,
     mut ball_query: Query<(&mut Ball, &Transform)>,
+    mut paddle_hit_event: EventWriter<PaddleHit>,
+    power_up: Res<ActivePowerUp>,
+    tuning: Res<Tuning>,
 ) {
     let (player, player_transform) = player_query.get_single().unwrap();
     let (mut ball, ball_transform) = ball_query.get_single_mut().unwrap();
 
+    if ball.caught.is_some() {
+        return;
+    }
+
     let collision = collide(
         player_transform.translation,
-        player.get_default_size(),
+        player.get_size(),
         ball_transform.translation,
         Vec2 {
-            x: ball.get_default_radius() * 2.,
-            y: ball.get_default_radius() * 2.,
+            x: ball.get_radius() * 2.,
+            y: ball.get_radius() * 2.,
         },
     );
 
-    if collision.is_some() {
-        match collision.unwrap() {
+    if let Some(collision) = collision {
+        let previous_direction = ball.direction;
+
+        match collision {
             Collision::Top => ball.direction.1 = -1,
             Collision::Bottom => ball.direction.1 = 1,
             Collision::Left => ball.direction.0 = 1,
             Collision::Right => ball.direction.0 = -1,
             _ => (),
         }
+
+        if ball.direction != previous_direction {
+            let distance = ball_transform.translation.x - player_transform.translation.x;
+            let offset = distance / (player.get_size().x / 2.);
+
+            if collision == Collision::Bottom && power_up.0 == Some(PowerUpKind::Catch) {
+                ball.caught = Some(CaughtBall {
+                    offset: distance,
+                    ticks_left: tuning.catch_release_ticks,
+                });
+            }
+
+            paddle_hit_event.send(PaddleHit {
+                offset: offset.clamp(-1., 1.),
+            });
+        }
     }
 }
@@ -0,0 +1,112 @@
+//! Broadphase for ball collisions.
+//!
+//! Bricks, walls and anything else the ball bounces off are bucketed in a
+//! uniform grid by their bounding box, so a ball only has to be tested
+//! against the colliders in the cells it overlaps.
+
+use bevy::{prelude::*, utils::HashMap};
+
+use super::GameplaySet;
+
+/// Side of a grid cell, a bit larger than a brick.
+const CELL_SIZE: f32 = 64.;
+
+pub struct ColliderGridPlugin;
+
+impl Plugin for ColliderGridPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ColliderGrid>().add_system(
+            update_collider_grid
+                .after(GameplaySet::Movement)
+                .before(GameplaySet::Collision)
+                .in_schedule(CoreSchedule::FixedUpdate),
+        );
+    }
+}
+
+/// Box the ball bounces off, centred on the entity's translation.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Collider {
+    pub size: Vec2,
+}
+
+/// Colliders by the grid cells their bounding box overlaps.
+///
+/// Spawned and moved colliders are added on the next gameplay tick, resizing
+/// one has to touch its transform too. Whatever despawns a collider removes
+/// it, looking up a despawned collider is harmless but wastes a test.
+#[derive(Resource, Default)]
+pub struct ColliderGrid {
+    cells: HashMap<IVec2, Vec<Entity>>,
+    /// First and last cell of every collider, and its centre.
+    bounds: HashMap<Entity, (IVec2, IVec2, Vec2)>,
+}
+
+impl ColliderGrid {
+    fn cell(position: Vec2) -> IVec2 {
+        (position / CELL_SIZE).floor().as_ivec2()
+    }
+
+    fn cells_between(min: IVec2, max: IVec2) -> impl Iterator<Item = IVec2> {
+        (min.y..=max.y).flat_map(move |y| (min.x..=max.x).map(move |x| IVec2::new(x, y)))
+    }
+
+    /// Adds a collider centred on `position`, or moves it there.
+    pub fn insert(&mut self, entity: Entity, position: Vec2, size: Vec2) {
+        self.remove(entity);
+
+        let min = Self::cell(position - size / 2.);
+        let max = Self::cell(position + size / 2.);
+        for cell in Self::cells_between(min, max) {
+            self.cells.entry(cell).or_default().push(entity);
+        }
+        self.bounds.insert(entity, (min, max, position));
+    }
+
+    pub fn remove(&mut self, entity: Entity) {
+        let Some((min, max, _)) = self.bounds.remove(&entity) else {
+            return;
+        };
+
+        for cell in Self::cells_between(min, max) {
+            if let Some(entities) = self.cells.get_mut(&cell) {
+                entities.retain(|&other| other != entity);
+                if entities.is_empty() {
+                    self.cells.remove(&cell);
+                }
+            }
+        }
+    }
+
+    /// Colliders whose cells overlap the box centred on `position`, each once.
+    ///
+    /// They are sorted bottom to top, then left to right, by their centre.
+    /// Entity ids depend on everything spawned outside the simulation too,
+    /// they only break ties between colliders sharing a centre.
+    pub fn query(&self, position: Vec2, size: Vec2) -> Vec<Entity> {
+        let min = Self::cell(position - size / 2.);
+        let max = Self::cell(position + size / 2.);
+
+        let mut entities: Vec<(Vec2, Entity)> = Self::cells_between(min, max)
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .map(|&entity| (self.bounds[&entity].2, entity))
+            .collect();
+        entities.sort_unstable_by(|(a, a_entity), (b, b_entity)| {
+            a.y.total_cmp(&b.y)
+                .then(a.x.total_cmp(&b.x))
+                .then(a_entity.cmp(b_entity))
+        });
+        entities.dedup_by_key(|(_, entity)| *entity);
+        entities.into_iter().map(|(_, entity)| entity).collect()
+    }
+}
+
+fn update_collider_grid(
+    mut grid: ResMut<ColliderGrid>,
+    colliders_query: Query<(Entity, &Collider, &Transform), Changed<Transform>>,
+) {
+    for (entity, collider, transform) in colliders_query.iter() {
+        grid.insert(entity, transform.translation.truncate(), collider.size);
+    }
+}
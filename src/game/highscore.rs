@@ -0,0 +1,125 @@
+//! Best scores of finished runs, kept in `high_scores.ron` next to the
+//! settings file.
+//!
+//! Only runs played live are recorded, along with the difficulty they were
+//! played on. Replays and the attract mode never are.
+
+use std::{fs, path::PathBuf};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{Difficulty, GameState, RunState};
+
+const HIGH_SCORES_FILE: &str = "high_scores.ron";
+/// Entries kept in the list.
+pub const MAX_HIGH_SCORES: usize = 10;
+
+pub struct HighScoresPlugin;
+
+impl Plugin for HighScoresPlugin {
+    fn build(&self, app: &mut App) {
+        let high_scores = HighScores::load().unwrap_or_else(|err| {
+            warn!(
+                "Ignoring invalid high scores {}: {}",
+                HighScores::path().display(),
+                err
+            );
+            HighScores::default()
+        });
+
+        // Recorded before the game over screen shows the list.
+        app.insert_resource(high_scores)
+            .add_system(record_high_score.in_schedule(OnExit(GameState::Playing)));
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct HighScore {
+    pub score: i32,
+    pub difficulty: Difficulty,
+    /// Id of the level the run ended on.
+    pub level: u32,
+    /// Whether the run finished the last level.
+    pub cleared: bool,
+}
+
+/// High scores, best first.
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct HighScores {
+    pub entries: Vec<HighScore>,
+}
+
+impl HighScores {
+    pub fn path() -> PathBuf {
+        dirs::config_dir()
+            .map(|dir| dir.join(env!("CARGO_PKG_NAME")))
+            .unwrap_or_default()
+            .join(HIGH_SCORES_FILE)
+    }
+
+    /// Reads the high scores file. A missing file means no high scores yet.
+    pub fn load() -> Result<Self, ron::error::SpannedError> {
+        match fs::read_to_string(Self::path()) {
+            Ok(text) => ron::from_str(&text),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::path();
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|err| err.to_string())?;
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+        }
+
+        fs::write(&path, text).map_err(|err| err.to_string())
+    }
+
+    /// Adds `entry` if it makes the list, returning its rank from `0`.
+    pub fn insert(&mut self, entry: HighScore) -> Option<usize> {
+        // Ties go below the scores already in the list.
+        let rank = self
+            .entries
+            .iter()
+            .position(|other| entry.score > other.score)
+            .unwrap_or(self.entries.len());
+        if rank >= MAX_HIGH_SCORES {
+            return None;
+        }
+
+        self.entries.insert(rank, entry);
+        self.entries.truncate(MAX_HIGH_SCORES);
+        Some(rank)
+    }
+}
+
+/// Records the run once it is over. Quitting to the menu mid-run saves it
+/// instead, it is recorded when it ends.
+fn record_high_score(run: Res<RunState>, mut high_scores: ResMut<HighScores>) {
+    if run.lives > 0 && !run.cleared {
+        return;
+    }
+
+    let entry = HighScore {
+        score: run.score,
+        difficulty: run.difficulty,
+        level: run.level,
+        cleared: run.cleared,
+    };
+
+    if high_scores.insert(entry).is_none() {
+        return;
+    }
+
+    if let Err(err) = high_scores.save() {
+        warn!(
+            "Could not save high scores to {}: {}",
+            HighScores::path().display(),
+            err
+        );
+    }
+}
@@ -3,7 +3,10 @@ use bevy::{
     prelude::*,
 };
 
-use super::{GameState, Health, Score, UpdateScore};
+use super::{
+    ComboChanged, Difficulty, GameState, HighScores, LivesChanged, RunState, SavedRun,
+    ScoreChanged, Settings, RUN_STATES,
+};
 
 #[derive(Component)]
 struct ScoreText;
@@ -21,20 +24,38 @@ struct GameplayHud;
 struct GameOverHud;
 
 #[derive(Component)]
-struct HealthText;
+struct LivesText;
+
+#[derive(Component)]
+struct ComboText;
+
+#[derive(Component)]
+struct ModeText;
+
+#[derive(Component)]
+struct MainMenuHud;
+
+#[derive(Component)]
+struct DifficultyText;
 
 pub struct GameHudPlugin;
 
 impl Plugin for GameHudPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugin(FrameTimeDiagnosticsPlugin::default())
-            .add_systems((
-                setup_hud.in_schedule(OnEnter(GameState::Playing)),
-                update_score_event.in_set(OnUpdate(GameState::Playing)),
-                update_fps.in_set(OnUpdate(GameState::Playing)),
-                update_health.in_set(OnUpdate(GameState::Playing)),
+        app.add_plugin(FrameTimeDiagnosticsPlugin)
+            .add_system(setup_replay_hud.in_schedule(OnEnter(GameState::Replaying)))
+            .add_system(setup_demo_hud.in_schedule(OnEnter(GameState::Demo)));
+
+        for state in RUN_STATES {
+            app.add_systems((
+                setup_hud.in_schedule(OnEnter(state.clone())),
+                update_score.in_set(OnUpdate(state.clone())),
+                update_fps.in_set(OnUpdate(state.clone())),
+                update_lives.in_set(OnUpdate(state.clone())),
+                update_combo.in_set(OnUpdate(state.clone())),
             ))
-            .add_system(despawn_screen::<GameplayHud>.in_schedule(OnExit(GameState::Playing)));
+            .add_system(despawn_screen::<GameplayHud>.in_schedule(OnExit(state)));
+        }
     }
 }
 
@@ -55,12 +76,93 @@ impl Plugin for GameOverPlugin {
     }
 }
 
+pub struct MainMenuPlugin;
+
+impl Plugin for MainMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(setup_main_menu.in_schedule(OnEnter(GameState::MainMenu)))
+            .add_system(update_difficulty.in_set(OnUpdate(GameState::MainMenu)))
+            .add_system(despawn_screen::<MainMenuHud>.in_schedule(OnExit(GameState::MainMenu)));
+    }
+}
+
+fn setup_main_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    saved_run: Res<SavedRun>,
+    difficulty: Res<Difficulty>,
+) {
+    let styled_menu_text = |font_size: f32| TextStyle {
+        font: asset_server.load("fonts/AtariST8x16SystemFont.ttf"),
+        font_size,
+        color: Color::WHITE,
+    };
+
+    commands.spawn((
+        TextBundle::from_sections([
+            TextSection::new("ARKANOID\n\n", styled_menu_text(80.)),
+            TextSection::new("Press ENTER to start", styled_menu_text(25.)),
+            TextSection::new(
+                if saved_run.0.is_some() {
+                    "\nPress C to continue"
+                } else {
+                    ""
+                },
+                styled_menu_text(25.),
+            ),
+            TextSection::new("\n\nDifficulty (LEFT/RIGHT)\n", styled_menu_text(20.)),
+            TextSection::new(difficulty_label(*difficulty), styled_menu_text(25.)),
+        ])
+        .with_text_alignment(TextAlignment::Center)
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            align_self: AlignSelf::Center,
+            position: UiRect {
+                left: Val::Percent(35.),
+                ..Default::default()
+            },
+            padding: UiRect::all(Val::Px(35.)),
+            ..default()
+        }),
+        DifficultyText,
+        MainMenuHud,
+    ));
+}
+
+fn update_difficulty(
+    difficulty: Res<Difficulty>,
+    mut query: Query<&mut Text, With<DifficultyText>>,
+) {
+    if !difficulty.is_changed() {
+        return;
+    }
+
+    for mut text in &mut query {
+        text.sections[4].value = difficulty_label(*difficulty);
+    }
+}
+
+fn difficulty_label(difficulty: Difficulty) -> String {
+    let previous = if difficulty == Difficulty::ALL[0] {
+        " "
+    } else {
+        "<"
+    };
+    let next = if difficulty == Difficulty::ALL[Difficulty::ALL.len() - 1] {
+        " "
+    } else {
+        ">"
+    };
+
+    format!("{} {} {}", previous, difficulty.name(), next)
+}
+
 fn setup_game_over(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
-    mut query_score: Query<&Score>,
+    run: Res<RunState>,
+    high_scores: Res<HighScores>,
 ) {
-    let score = query_score.get_single_mut();
     let styled_game_over_text = |font_size: f32| TextStyle {
         font: asset_server.load("fonts/AtariST8x16SystemFont.ttf"),
         font_size,
@@ -70,11 +172,23 @@ fn setup_game_over(
     commands.spawn((
         TextBundle::from_sections([
             TextSection::new(
-                format!("Game Over\nFinal score: {:?}\n\n", score.unwrap().0),
+                format!(
+                    "{}\nFinal score: {:?} ({})\n\n",
+                    if run.cleared {
+                        "Level Cleared"
+                    } else {
+                        "Game Over"
+                    },
+                    run.score,
+                    run.difficulty.name()
+                ),
                 styled_game_over_text(45.),
             ),
-            TextSection::new("Press ESC to restart the game", styled_game_over_text(15.)),
-            TextSection::from_style(styled_game_over_text(35.)),
+            TextSection::new(
+                "Press ESC to restart the game\nPress R to watch the replay",
+                styled_game_over_text(15.),
+            ),
+            TextSection::new(high_score_lines(&high_scores), styled_game_over_text(15.)),
         ])
         .with_text_alignment(TextAlignment::Center)
         .with_style(Style {
@@ -92,7 +206,29 @@ fn setup_game_over(
     ));
 }
 
-fn setup_hud(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn high_score_lines(high_scores: &HighScores) -> String {
+    if high_scores.entries.is_empty() {
+        return String::new();
+    }
+
+    let lines: Vec<String> = high_scores
+        .entries
+        .iter()
+        .enumerate()
+        .map(|(rank, entry)| {
+            format!(
+                "{:>2}. {:>7} {}",
+                rank + 1,
+                entry.score,
+                entry.difficulty.name()
+            )
+        })
+        .collect();
+
+    format!("\n\nHigh scores\n{}", lines.join("\n"))
+}
+
+fn setup_hud(mut commands: Commands, asset_server: Res<AssetServer>, settings: Res<Settings>) {
     let styled_text = |font_size: f32| TextStyle {
         font: asset_server.load("fonts/AtariST8x16SystemFont.ttf"),
         font_size,
@@ -119,7 +255,7 @@ fn setup_hud(mut commands: Commands, asset_server: Res<AssetServer>) {
 
     commands.spawn((
         TextBundle::from_sections([
-            TextSection::new("Health: ", styled_text(45.)),
+            TextSection::new("Lives: ", styled_text(45.)),
             TextSection::from_style(styled_text(45.)),
         ])
         .with_text_alignment(TextAlignment::Center)
@@ -132,46 +268,124 @@ fn setup_hud(mut commands: Commands, asset_server: Res<AssetServer>) {
             },
             ..default()
         }),
-        HealthText,
+        LivesText,
         GameplayHud,
     ));
 
     commands.spawn((
         TextBundle::from_sections([
-            TextSection::new("FPS: ", styled_text(20.)),
-            TextSection::from_style(styled_text(20.)),
+            TextSection::new("Combo: ", styled_text(25.)),
+            TextSection::from_style(styled_text(25.)),
+            TextSection::from_style(TextStyle {
+                color: Color::GOLD,
+                ..styled_text(25.)
+            }),
         ])
         .with_text_alignment(TextAlignment::Center)
         .with_style(Style {
             position_type: PositionType::Absolute,
             position: UiRect {
-                top: Val::Px(5.0),
+                bottom: Val::Px(90.0),
                 right: Val::Px(15.0),
                 ..default()
             },
             ..default()
         }),
-        FpsText,
+        ComboText,
         GameplayHud,
     ));
+
+    if settings.display.show_fps {
+        commands.spawn((
+            TextBundle::from_sections([
+                TextSection::new("FPS: ", styled_text(20.)),
+                TextSection::from_style(styled_text(20.)),
+            ])
+            .with_text_alignment(TextAlignment::Center)
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(5.0),
+                    right: Val::Px(15.0),
+                    ..default()
+                },
+                ..default()
+            }),
+            FpsText,
+            GameplayHud,
+        ));
+    }
 }
 
-fn update_health(mut query: Query<&mut Text, With<HealthText>>, mut query_health: Query<&Health>) {
-    let health = query_health.single_mut();
+fn setup_replay_hud(commands: Commands, asset_server: Res<AssetServer>) {
+    spawn_mode_text(commands, asset_server, "REPLAY - press ESC to stop");
+}
+
+fn setup_demo_hud(commands: Commands, asset_server: Res<AssetServer>) {
+    spawn_mode_text(commands, asset_server, "DEMO - press any key");
+}
+
+fn spawn_mode_text(mut commands: Commands, asset_server: Res<AssetServer>, text: &str) {
+    commands.spawn((
+        TextBundle::from_section(
+            text,
+            TextStyle {
+                font: asset_server.load("fonts/AtariST8x16SystemFont.ttf"),
+                font_size: 20.,
+                color: Color::WHITE,
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                top: Val::Px(5.0),
+                left: Val::Px(15.0),
+                ..default()
+            },
+            ..default()
+        }),
+        ModeText,
+        GameplayHud,
+    ));
+}
+
+fn update_lives(
+    mut lives_changed_events: EventReader<LivesChanged>,
+    mut query: Query<&mut Text, With<LivesText>>,
+) {
+    let Some(lives) = lives_changed_events.iter().last() else {
+        return;
+    };
+
     for mut text in &mut query {
-        text.sections[1].value = format!("{}", health.0);
+        text.sections[1].value = format!("{}", lives.total);
     }
 }
 
-fn update_score_event(
-    mut _event: EventReader<UpdateScore>,
+fn update_score(
+    mut score_changed_events: EventReader<ScoreChanged>,
     mut query: Query<&mut Text, With<ScoreText>>,
-    mut query_score: Query<&Score>,
 ) {
-    let score = query_score.single_mut();
+    let Some(score) = score_changed_events.iter().last() else {
+        return;
+    };
+
+    for mut text in &mut query {
+        text.sections[1].value = format!("{}", score.total);
+    }
+}
+
+fn update_combo(
+    mut combo_changed_events: EventReader<ComboChanged>,
+    mut query: Query<&mut Text, With<ComboText>>,
+) {
+    let Some(combo) = combo_changed_events.iter().last() else {
+        return;
+    };
 
     for mut text in &mut query {
-        text.sections[1].value = format!("{}", score.0);
+        text.sections[1].value = format!("{} ", combo.combo);
+        text.sections[2].value = format!("x{}", combo.multiplier);
     }
 }
 
@@ -0,0 +1,193 @@
+//! Laser bolts fired from the paddle while the Laser power-up is active.
+//!
+//! Every shot fires a bolt from both edges of the paddle. Bolts fly straight
+//! up and stop at the first brick or wall they touch, damaging the brick.
+
+use bevy::{
+    prelude::*,
+    sprite::{collide_aabb::collide, MaterialMesh2dBundle, Mesh2dHandle},
+};
+
+use super::{
+    ActivePowerUp, ArenaWall, Brick, BrickHit, Collider, ColliderGrid, GameplaySet, LevelAdvanced,
+    Player, PlayerActions, PowerUpKind, Tuning, RUN_STATES,
+};
+
+const BOLT_SIZE: Vec2 = Vec2::new(4., 14.);
+const BOLT_COLOR: Color = Color::rgb(1., 0.2, 0.2);
+/// Distance from the paddle edges to the bolts they fire.
+const BOLT_INSET: f32 = 6.;
+
+pub struct LaserPlugin;
+
+impl Plugin for LaserPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LaserCannon>()
+            .add_startup_system(load_bolt_assets)
+            .add_system(
+                fire_lasers
+                    .in_set(GameplaySet::Input)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            )
+            .add_system(
+                update_bolt_movement
+                    .in_set(GameplaySet::Movement)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            )
+            .add_system(
+                bolt_collision
+                    .in_set(GameplaySet::Collision)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            )
+            .add_system(
+                reset
+                    .run_if(on_event::<LevelAdvanced>())
+                    .in_set(GameplaySet::NextLevel)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            );
+
+        for state in RUN_STATES {
+            app.add_system(reset.in_schedule(OnExit(state)));
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct LaserBolt;
+
+/// Ticks left before the paddle can fire again.
+#[derive(Resource, Default)]
+pub struct LaserCannon {
+    pub cooldown: u32,
+}
+
+#[derive(Resource)]
+struct BoltAssets {
+    mesh: Mesh2dHandle,
+    material: Handle<ColorMaterial>,
+}
+
+fn load_bolt_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    commands.insert_resource(BoltAssets {
+        mesh: meshes.add(shape::Quad::new(BOLT_SIZE).into()).into(),
+        material: materials.add(ColorMaterial::from(BOLT_COLOR)),
+    });
+}
+
+fn fire_lasers(
+    mut commands: Commands,
+    mut cannon: ResMut<LaserCannon>,
+    actions: Res<PlayerActions>,
+    power_up: Res<ActivePowerUp>,
+    player_query: Query<(&Player, &Transform)>,
+    assets: Res<BoltAssets>,
+    tuning: Res<Tuning>,
+) {
+    cannon.cooldown = cannon.cooldown.saturating_sub(1);
+
+    if !actions.fire || cannon.cooldown > 0 || power_up.0 != Some(PowerUpKind::Laser) {
+        return;
+    }
+
+    let (player, transform) = player_query.single();
+    let half_width = player.get_size().x / 2. - BOLT_INSET;
+    let top = transform.translation.y + (player.get_size().y + BOLT_SIZE.y) / 2.;
+
+    for x in [-half_width, half_width] {
+        commands.spawn((
+            MaterialMesh2dBundle {
+                mesh: assets.mesh.clone(),
+                material: assets.material.clone(),
+                transform: Transform::from_xyz(transform.translation.x + x, top, 0.),
+                ..default()
+            },
+            LaserBolt,
+            Name::new("LaserBolt"),
+        ));
+    }
+    cannon.cooldown = tuning.laser_cooldown_ticks;
+}
+
+fn update_bolt_movement(
+    mut bolts_query: Query<&mut Transform, With<LaserBolt>>,
+    tuning: Res<Tuning>,
+) {
+    for mut transform in bolts_query.iter_mut() {
+        transform.translation.y += tuning.laser_speed;
+    }
+}
+
+/// Stops every bolt at the first brick or wall it touches, damaging the
+/// brick.
+fn bolt_collision(
+    mut commands: Commands,
+    bolts_query: Query<(Entity, &Transform), With<LaserBolt>>,
+    mut colliders_query: Query<(
+        &Collider,
+        &Transform,
+        Option<&mut Brick>,
+        Option<&ArenaWall>,
+    )>,
+    mut brick_hit_event: EventWriter<BrickHit>,
+    collider_grid: Res<ColliderGrid>,
+    tuning: Res<Tuning>,
+) {
+    for (bolt, bolt_transform) in bolts_query.iter() {
+        let nearby_colliders =
+            collider_grid.query(bolt_transform.translation.truncate(), BOLT_SIZE);
+
+        for entity in nearby_colliders {
+            let Ok((collider, collider_transform, brick, wall)) = colliders_query.get_mut(entity)
+            else {
+                continue;
+            };
+
+            if brick.is_none() && wall.is_none() {
+                continue;
+            }
+
+            let touching = collide(
+                bolt_transform.translation,
+                BOLT_SIZE,
+                collider_transform.translation,
+                collider.size,
+            )
+            .is_some();
+            if !touching {
+                continue;
+            }
+
+            if let Some(mut brick) = brick {
+                // Already destroyed by the ball or another bolt this tick.
+                if brick.health() <= 0. {
+                    continue;
+                }
+
+                brick.apply_damage(tuning.laser_damage);
+                brick_hit_event.send(BrickHit {
+                    brick: brick.entity,
+                    damage: tuning.laser_damage,
+                    by_ball: false,
+                });
+            }
+
+            commands.entity(bolt).despawn();
+            break;
+        }
+    }
+}
+
+fn reset(
+    mut commands: Commands,
+    bolts_query: Query<Entity, With<LaserBolt>>,
+    mut cannon: ResMut<LaserCannon>,
+) {
+    for entity in bolts_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    cannon.cooldown = 0;
+}
@@ -0,0 +1,252 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use super::{BossAttack, BossSpec, EnemyBehavior, EnemySpawn};
+
+/// A brick layout. Every row of `layout` is one row of bricks, where `.`
+/// leaves a gap and any other character places the brick of that
+/// [`BrickKind`](super::BrickKind), `#` being a plain white brick.
+pub struct Level {
+    pub id: u32,
+    pub name: &'static str,
+    pub layout: &'static [&'static str],
+    /// Background music, relative to the assets folder.
+    pub music: &'static str,
+    /// Clearing the level faster than this earns a bonus for every second
+    /// left.
+    pub par_seconds: f32,
+    /// Rows of `layout` whose bricks move, and how. Rows left out stay put.
+    pub motions: &'static [(usize, BrickMotion)],
+    /// Where enemies come in from, in turn. No enemies show up when empty.
+    pub enemy_spawns: &'static [EnemySpawn],
+    /// The boss guarding the level. Defeating it clears the level.
+    pub boss: Option<BossSpec>,
+}
+
+/// How the bricks of a row move, as an offset from their cell that only
+/// depends on how long the level has been played. Moving bricks are then
+/// in the same place after resuming a run or in its replay.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BrickMotion {
+    /// Slides sideways up to `reach` pixels either side of the cell at
+    /// `speed` pixels per tick, starting towards the side `reach` points to.
+    Slide { reach: f32, speed: f32 },
+    /// Swings smoothly up to `amplitude` either side of the cell, once every
+    /// `period_ticks`.
+    Oscillate { amplitude: Vec2, period_ticks: u32 },
+    /// Loops through `points`, offsets from the cell, and back to the cell at
+    /// `speed` pixels per tick.
+    Path { points: &'static [Vec2], speed: f32 },
+}
+
+impl BrickMotion {
+    /// Offset from the cell after `ticks` ticks of the level.
+    pub fn offset(&self, ticks: u64) -> Vec2 {
+        match *self {
+            Self::Slide { reach, speed } => {
+                let distance = reach.abs();
+                if distance == 0. || speed <= 0. {
+                    return Vec2::ZERO;
+                }
+
+                let travelled = (ticks as f64 * speed as f64 % (4. * distance as f64)) as f32;
+                let x = if travelled < distance {
+                    travelled
+                } else if travelled < 3. * distance {
+                    2. * distance - travelled
+                } else {
+                    travelled - 4. * distance
+                };
+                Vec2::new(x * reach.signum(), 0.)
+            }
+            Self::Oscillate {
+                amplitude,
+                period_ticks,
+            } => {
+                if period_ticks == 0 {
+                    return Vec2::ZERO;
+                }
+
+                let phase = (ticks % period_ticks as u64) as f32 / period_ticks as f32;
+                amplitude * (phase * TAU).sin()
+            }
+            Self::Path { points, speed } => {
+                let corners = || {
+                    std::iter::once(Vec2::ZERO)
+                        .chain(points.iter().copied())
+                        .chain(std::iter::once(Vec2::ZERO))
+                };
+                let length: f32 = corners()
+                    .zip(corners().skip(1))
+                    .map(|(from, to)| from.distance(to))
+                    .sum();
+                if length == 0. || speed <= 0. {
+                    return Vec2::ZERO;
+                }
+
+                let mut travelled = (ticks as f64 * speed as f64 % length as f64) as f32;
+                for (from, to) in corners().zip(corners().skip(1)) {
+                    let segment = from.distance(to);
+                    if segment == 0. {
+                        continue;
+                    }
+                    if travelled <= segment {
+                        return from.lerp(to, travelled / segment);
+                    }
+                    travelled -= segment;
+                }
+                Vec2::ZERO
+            }
+        }
+    }
+}
+
+pub const LEVELS: &[Level] = &[
+    Level {
+        id: 1,
+        name: "Classic",
+        layout: &[
+            "###################",
+            "###################",
+            "###################",
+            "###################",
+            "###################",
+        ],
+        music: "music/level_1.wav",
+        par_seconds: 240.,
+        motions: &[],
+        enemy_spawns: &[],
+        boss: None,
+    },
+    Level {
+        id: 2,
+        name: "Drift",
+        layout: &[
+            "sssssssssssssssssss",
+            "....ccccccccccc....",
+            "...................",
+            "..ggggg.....ggggg..",
+            "....rrrrrrrrrrr....",
+            "........yyy........",
+        ],
+        music: "music/level_2.wav",
+        par_seconds: 300.,
+        motions: &[
+            (
+                1,
+                BrickMotion::Slide {
+                    reach: 200.,
+                    speed: 1.,
+                },
+            ),
+            (
+                3,
+                BrickMotion::Oscillate {
+                    amplitude: Vec2::new(0., 12.),
+                    period_ticks: 180,
+                },
+            ),
+            (
+                4,
+                BrickMotion::Slide {
+                    reach: -200.,
+                    speed: 1.5,
+                },
+            ),
+            (
+                5,
+                BrickMotion::Path {
+                    points: &[
+                        Vec2::new(-300., 0.),
+                        Vec2::new(-300., -60.),
+                        Vec2::new(300., -60.),
+                        Vec2::new(300., 0.),
+                    ],
+                    speed: 2.,
+                },
+            ),
+        ],
+        enemy_spawns: &[
+            EnemySpawn {
+                x: -320.,
+                behavior: EnemyBehavior::Weave,
+            },
+            EnemySpawn {
+                x: 320.,
+                behavior: EnemyBehavior::Chase,
+            },
+            EnemySpawn {
+                x: 0.,
+                behavior: EnemyBehavior::Fall,
+            },
+        ],
+        boss: None,
+    },
+    Level {
+        id: 3,
+        name: "Doh",
+        layout: &[],
+        music: "music/level_3.wav",
+        par_seconds: 180.,
+        motions: &[],
+        enemy_spawns: &[],
+        boss: Some(BossSpec {
+            name: "Doh",
+            health: 1200.,
+            position: Vec2::new(0., 110.),
+            parts: &[
+                (Vec2::new(0., 0.), Vec2::new(160., 200.)),
+                (Vec2::new(-100., 10.), Vec2::new(40., 140.)),
+                (Vec2::new(100., 10.), Vec2::new(40., 140.)),
+                (Vec2::new(0., -120.), Vec2::new(80., 40.)),
+            ],
+            attacks: &[
+                BossAttack::Aimed,
+                BossAttack::Spread {
+                    count: 3,
+                    angle: 0.6,
+                },
+                BossAttack::Shot,
+                BossAttack::Aimed,
+            ],
+        }),
+    },
+];
+
+impl Level {
+    pub fn get(id: u32) -> Option<&'static Level> {
+        LEVELS.iter().find(|level| level.id == id)
+    }
+
+    /// The level played after this one, if this is not the last.
+    pub fn next(&self) -> Option<&'static Level> {
+        let index = LEVELS.iter().position(|level| level.id == self.id)?;
+        LEVELS.get(index + 1)
+    }
+
+    /// How the bricks of a row move, if they do.
+    pub fn motion(&self, row: usize) -> Option<BrickMotion> {
+        self.motions
+            .iter()
+            .find(|(motion_row, _)| *motion_row == row)
+            .map(|(_, motion)| *motion)
+    }
+}
+
+/// Id of the level runs start on. The run then moves on to the next levels
+/// through [`RunState::level`](super::RunState::level).
+#[derive(Resource, Clone, Copy, Eq, PartialEq, Debug)]
+pub struct CurrentLevel(pub u32);
+
+impl Default for CurrentLevel {
+    fn default() -> Self {
+        Self(LEVELS[0].id)
+    }
+}
+
+impl CurrentLevel {
+    pub fn level(&self) -> &'static Level {
+        Level::get(self.0).unwrap_or(&LEVELS[0])
+    }
+}
@@ -1,11 +1,53 @@
+mod ai;
+mod arena;
+mod audio;
 mod ball;
+mod boss;
+mod bytes;
+mod demo;
+mod difficulty;
+mod effects;
+mod enemy;
+mod events;
+#[allow(clippy::module_inception)]
 mod game;
+mod grid;
+mod highscore;
 mod hud;
+mod laser;
+mod level;
 mod player;
+mod powerup;
+mod replay;
+mod save;
+mod scoring;
+mod settings;
+mod theme;
+mod tuning;
 mod wall;
 
+pub use ai::*;
+pub use arena::*;
+pub use audio::*;
 pub use ball::*;
+pub use boss::*;
+pub use demo::*;
+pub use difficulty::*;
+pub use effects::*;
+pub use enemy::*;
+pub use events::*;
 pub use game::*;
+pub use grid::*;
+pub use highscore::*;
 pub use hud::*;
+pub use laser::*;
+pub use level::*;
 pub use player::*;
+pub use powerup::*;
+pub use replay::*;
+pub use save::*;
+pub use scoring::*;
+pub use settings::*;
+pub use theme::*;
+pub use tuning::*;
 pub use wall::*;
@@ -1,21 +1,115 @@
-use super::GameState;
+use super::{
+    reset_run, AiController, Arena, Difficulty, GameState, GameplaySet, LevelAdvanced, RunState,
+    Settings, Skin, ThemePart, Tuning, WarpGate, RUN_STATES, WALL_THICKNESS,
+};
 use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
 
-const MOVEMENT_SPEED_BOOST: f32 = 2.;
-const MOVEMENT_SPEED: f32 = 1.5;
+/// Size of the paddle mesh, scaled to the size picked by the difficulty.
 const PLAYER_PADDLE_SIZE: Vec2 = Vec2 { x: 100., y: 20. };
 
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_startup_system(initialize)
-            .add_systems((
-                process_player_input.in_set(OnUpdate(GameState::Playing)),
-                update_player_movement.in_set(OnUpdate(GameState::Playing)),
-                process_player_collision.in_set(OnUpdate(GameState::Playing)),
-            ))
-            .add_system(reset.in_schedule(OnExit(GameState::Playing)));
+        app.init_resource::<PlayerActions>()
+            .add_startup_system(initialize)
+            .add_system(apply_tuning.run_if(resource_changed::<Tuning>()))
+            .add_system(
+                read_keyboard_actions
+                    .run_if(
+                        in_state(GameState::Playing)
+                            .and_then(not(resource_exists::<AiController>())),
+                    )
+                    .in_set(GameplaySet::ReadActions)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            )
+            .add_system(
+                process_player_input
+                    .in_set(GameplaySet::Input)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            )
+            .add_system(
+                update_player_movement
+                    .in_set(GameplaySet::Movement)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            )
+            .add_system(
+                process_player_collision
+                    .in_set(GameplaySet::Collision)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            );
+
+        for state in RUN_STATES {
+            app.add_system(
+                apply_difficulty
+                    .after(reset_run)
+                    .in_schedule(OnEnter(state.clone())),
+            )
+            .add_system(reset.in_schedule(OnExit(state)));
+        }
+
+        app.add_system(
+            reset
+                .run_if(on_event::<LevelAdvanced>())
+                .in_set(GameplaySet::NextLevel)
+                .in_schedule(CoreSchedule::FixedUpdate),
+        );
+    }
+}
+
+/// What the player asked the paddle to do during the current gameplay tick.
+/// Filled from the keyboard while playing and from the replay while replaying.
+#[derive(Resource, Clone, Copy, Default, Eq, PartialEq, Debug)]
+pub struct PlayerActions {
+    pub left: bool,
+    pub right: bool,
+    pub boost: bool,
+    /// Fire the lasers, when the paddle has them.
+    pub fire: bool,
+    /// Let go of the ball caught on the paddle.
+    pub launch: bool,
+}
+
+impl PlayerActions {
+    const LEFT: u8 = 1;
+    const RIGHT: u8 = 1 << 1;
+    const BOOST: u8 = 1 << 2;
+    const FIRE: u8 = 1 << 3;
+    const LAUNCH: u8 = 1 << 4;
+
+    pub fn to_bits(self) -> u8 {
+        let mut bits = 0;
+        if self.left {
+            bits |= Self::LEFT;
+        }
+        if self.right {
+            bits |= Self::RIGHT;
+        }
+        if self.boost {
+            bits |= Self::BOOST;
+        }
+        if self.fire {
+            bits |= Self::FIRE;
+        }
+        if self.launch {
+            bits |= Self::LAUNCH;
+        }
+        bits
+    }
+
+    /// Returns `None` if `bits` has flags set that no action maps to.
+    pub fn try_from_bits(bits: u8) -> Option<Self> {
+        if bits & !(Self::LEFT | Self::RIGHT | Self::BOOST | Self::FIRE | Self::LAUNCH) != 0 {
+            return None;
+        }
+
+        Some(Self {
+            left: bits & Self::LEFT != 0,
+            right: bits & Self::RIGHT != 0,
+            boost: bits & Self::BOOST != 0,
+            fire: bits & Self::FIRE != 0,
+            launch: bits & Self::LAUNCH != 0,
+        })
     }
 }
 
@@ -25,26 +119,24 @@ pub struct Player {
     can_move_left: bool,
     can_move_right: bool,
     boosting: bool,
+    size: Vec2,
+    speed: f32,
+    boost_speed: f32,
 }
 
 impl Player {
-    pub fn get_default_size(&self) -> Vec2 {
-        PLAYER_PADDLE_SIZE
+    pub fn get_size(&self) -> Vec2 {
+        self.size
     }
 }
 
 #[derive(Clone)]
 enum PlayerDirection {
-    LEFT,
-    RIGHT,
+    Left,
+    Right,
 }
 
-fn reset(
-    mut player_query: Query<(&mut Player, &mut Transform)>,
-    mut window_query: Query<&mut Window>,
-) {
-    let window = window_query.get_single_mut().unwrap();
-
+fn reset(mut player_query: Query<(&mut Player, &mut Transform)>, arena: Res<Arena>) {
     let (mut player, mut player_transform) = player_query.get_single_mut().unwrap();
 
     player.boosting = false;
@@ -54,24 +146,59 @@ fn reset(
 
     let spawn_position = Vec3 {
         x: 0.,
-        y: -(window.height() / 2.0) + 100.,
+        y: -arena.half_size().y + 100.,
         z: 0.,
     };
 
     *player_transform = Transform::from_translation(spawn_position);
 }
 
+fn apply_difficulty(
+    mut player_query: Query<(&mut Player, &mut Transform)>,
+    run: Res<RunState>,
+    tuning: Res<Tuning>,
+) {
+    let (mut player, mut player_transform) = player_query.get_single_mut().unwrap();
+    set_paddle_size_and_speed(&mut player, &mut player_transform, &tuning, run.difficulty);
+}
+
+/// Resizes the paddle and changes its speed when they are tuned for the
+/// difficulty of the run.
+fn apply_tuning(
+    mut player_query: Query<(&mut Player, &mut Transform)>,
+    run: Res<RunState>,
+    tuning: Res<Tuning>,
+) {
+    for (mut player, mut player_transform) in player_query.iter_mut() {
+        set_paddle_size_and_speed(&mut player, &mut player_transform, &tuning, run.difficulty);
+    }
+}
+
+fn set_paddle_size_and_speed(
+    player: &mut Player,
+    player_transform: &mut Transform,
+    tuning: &Tuning,
+    difficulty: Difficulty,
+) {
+    let difficulty_tuning = tuning.difficulty(difficulty);
+
+    player.size = difficulty_tuning.paddle_size;
+    player.speed = difficulty_tuning.paddle_speed;
+    player.boost_speed = tuning.paddle_boost_speed;
+
+    player_transform.scale = (player.size / PLAYER_PADDLE_SIZE).extend(1.);
+}
+
 fn initialize(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
-    mut window_query: Query<&mut Window>,
+    arena: Res<Arena>,
+    tuning: Res<Tuning>,
 ) {
-    let window = window_query.get_single_mut().unwrap();
-
     let spawn_position = Vec3 {
         x: 0.,
-        y: -(window.height() / 2.0) + 100.,
+        y: -arena.half_size().y + 100.,
         z: 0.,
     };
 
@@ -93,55 +220,77 @@ fn initialize(
             boosting: false,
             can_move_left: true,
             can_move_right: true,
+            size: PLAYER_PADDLE_SIZE,
+            speed: tuning.difficulty(Difficulty::default()).paddle_speed,
+            boost_speed: tuning.paddle_boost_speed,
+        })
+        .insert(Skin {
+            part: ThemePart::Paddle,
+            size: PLAYER_PADDLE_SIZE,
         })
         .insert(Name::new("PlayerPaddle"));
 }
 
-fn process_player_input(mut player_query: Query<&mut Player>, keyboard_input: Res<Input<KeyCode>>) {
-    let mut player = player_query.get_single_mut().unwrap();
+fn read_keyboard_actions(
+    mut actions: ResMut<PlayerActions>,
+    keyboard_input: Res<Input<KeyCode>>,
+    settings: Res<Settings>,
+) {
+    let controls = &settings.controls;
 
-    player.boosting = if keyboard_input.pressed(KeyCode::LShift) {
-        true
-    } else {
-        false
+    *actions = PlayerActions {
+        left: keyboard_input.pressed(controls.left),
+        right: keyboard_input.pressed(controls.right),
+        boost: keyboard_input.pressed(controls.boost),
+        fire: keyboard_input.pressed(controls.fire),
+        launch: keyboard_input.pressed(controls.launch),
     };
+}
+
+fn process_player_input(mut player_query: Query<&mut Player>, actions: Res<PlayerActions>) {
+    let mut player = player_query.get_single_mut().unwrap();
+
+    player.boosting = actions.boost;
 
-    if keyboard_input.pressed(KeyCode::A) && player.can_move_left {
-        player.direction = Some(PlayerDirection::LEFT);
+    if actions.left && player.can_move_left {
+        player.direction = Some(PlayerDirection::Left);
         player.can_move_right = true;
     }
 
-    if keyboard_input.pressed(KeyCode::D) && player.can_move_right {
-        player.direction = Some(PlayerDirection::RIGHT);
+    if actions.right && player.can_move_right {
+        player.direction = Some(PlayerDirection::Right);
         player.can_move_left = true;
     }
 }
 
-fn update_player_movement(mut player_query: Query<(&Player, &mut Transform)>) {
+pub fn update_player_movement(mut player_query: Query<(&Player, &mut Transform)>) {
     let (player, mut transform) = player_query.get_single_mut().unwrap();
 
     let final_speed = match player.boosting {
-        true => MOVEMENT_SPEED + MOVEMENT_SPEED_BOOST,
-        false => MOVEMENT_SPEED,
+        true => player.speed + player.boost_speed,
+        false => player.speed,
     };
 
     match player.direction {
         None => {}
-        Some(PlayerDirection::LEFT) => transform.translation.x -= final_speed,
-        Some(PlayerDirection::RIGHT) => transform.translation.x += final_speed,
+        Some(PlayerDirection::Left) => transform.translation.x -= final_speed,
+        Some(PlayerDirection::Right) => transform.translation.x += final_speed,
     }
 }
 
+/// Stops the paddle at the side walls, unless it is heading out through an
+/// open warp gate on the right.
 fn process_player_collision(
     mut player_query: Query<(&mut Player, &Transform)>,
-    window_query: Query<&Window>,
+    gates_query: Query<&WarpGate>,
+    arena: Res<Arena>,
 ) {
-    let window = window_query.get_single().unwrap();
     let (mut player, transform) = player_query.get_single_mut().unwrap();
+    let gate_open = gates_query.iter().any(|gate| gate.open);
 
-    let limit_x = (window.width() / 2.0) - (PLAYER_PADDLE_SIZE.x / 2.);
+    let limit_x = arena.half_size().x - WALL_THICKNESS - (player.size.x / 2.);
 
-    if transform.translation.x + 10. > limit_x {
+    if transform.translation.x + 10. > limit_x && !gate_open {
         player.direction = None;
         player.can_move_right = false;
         player.can_move_left = true;
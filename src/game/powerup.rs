@@ -0,0 +1,195 @@
+//! Power-up capsules dropped by destroyed bricks.
+//!
+//! Whether a brick drops a capsule and which one is rolled with
+//! [`GameRng`], so replays drop the same capsules. Only one capsule falls at
+//! a time, and catching one replaces the power-up in use.
+
+use bevy::{
+    prelude::*,
+    sprite::{collide_aabb::collide, MaterialMesh2dBundle, Mesh2dHandle},
+    utils::HashMap,
+};
+use rand::Rng;
+
+use super::{
+    bricks_health_check, Arena, BrickDestroyed, GameRng, GameplaySet, LevelAdvanced, Player,
+    PowerUpCollected, Tuning, RUN_STATES,
+};
+
+const CAPSULE_SIZE: Vec2 = Vec2::new(32., 14.);
+
+pub struct PowerUpPlugin;
+
+impl Plugin for PowerUpPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActivePowerUp>()
+            .add_startup_system(load_capsule_assets)
+            .add_system(
+                update_capsule_movement
+                    .in_set(GameplaySet::Movement)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            )
+            .add_system(
+                collect_capsules
+                    .in_set(GameplaySet::Collision)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            )
+            .add_system(
+                drop_capsules
+                    .after(bricks_health_check)
+                    .in_set(GameplaySet::Cleanup)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            )
+            .add_system(
+                reset
+                    .run_if(on_event::<LevelAdvanced>())
+                    .in_set(GameplaySet::NextLevel)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            );
+
+        for state in RUN_STATES {
+            app.add_system(reset.in_schedule(OnExit(state)));
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum PowerUpKind {
+    /// Opens the warp gate in the right wall.
+    Break,
+    /// Lets the paddle fire lasers.
+    Laser,
+    /// Makes the ball stick to the paddle until it is launched.
+    Catch,
+}
+
+impl PowerUpKind {
+    pub const ALL: [PowerUpKind; 3] = [PowerUpKind::Break, PowerUpKind::Laser, PowerUpKind::Catch];
+
+    pub fn color(&self) -> Color {
+        match self {
+            Self::Break => Color::rgb(1., 0.4, 0.75),
+            Self::Laser => Color::rgb(0.9, 0.15, 0.15),
+            Self::Catch => Color::rgb(0.2, 0.85, 0.2),
+        }
+    }
+
+    pub fn to_index(self) -> u8 {
+        Self::ALL.iter().position(|&kind| kind == self).unwrap() as u8
+    }
+
+    pub fn from_index(index: u8) -> Option<Self> {
+        Self::ALL.get(index as usize).copied()
+    }
+}
+
+/// The power-up caught last, until the level ends.
+#[derive(Resource, Clone, Copy, Default, Debug, PartialEq)]
+pub struct ActivePowerUp(pub Option<PowerUpKind>);
+
+#[derive(Component)]
+pub struct Capsule {
+    pub kind: PowerUpKind,
+}
+
+#[derive(Resource)]
+struct CapsuleAssets {
+    mesh: Mesh2dHandle,
+    materials: HashMap<PowerUpKind, Handle<ColorMaterial>>,
+}
+
+fn load_capsule_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    commands.insert_resource(CapsuleAssets {
+        mesh: meshes.add(shape::Quad::new(CAPSULE_SIZE).into()).into(),
+        materials: PowerUpKind::ALL
+            .into_iter()
+            .map(|kind| (kind, materials.add(ColorMaterial::from(kind.color()))))
+            .collect(),
+    });
+}
+
+/// Rolls a capsule for every destroyed brick while none is falling.
+fn drop_capsules(
+    mut commands: Commands,
+    mut brick_destroyed_events: EventReader<BrickDestroyed>,
+    capsules_query: Query<(), With<Capsule>>,
+    mut rng: ResMut<GameRng>,
+    assets: Res<CapsuleAssets>,
+    tuning: Res<Tuning>,
+) {
+    let mut falling = !capsules_query.is_empty();
+
+    for brick in brick_destroyed_events.iter() {
+        if falling || !rng.0.gen_bool(tuning.power_up_chance.clamp(0., 1.)) {
+            continue;
+        }
+
+        let kind = PowerUpKind::ALL[rng.0.gen_range(0..PowerUpKind::ALL.len())];
+        commands.spawn((
+            MaterialMesh2dBundle {
+                mesh: assets.mesh.clone(),
+                material: assets.materials[&kind].clone(),
+                transform: Transform::from_translation(brick.position.extend(0.)),
+                ..default()
+            },
+            Capsule { kind },
+            Name::new(format!("Capsule-{:?}", kind)),
+        ));
+        falling = true;
+    }
+}
+
+fn update_capsule_movement(
+    mut capsules_query: Query<&mut Transform, With<Capsule>>,
+    tuning: Res<Tuning>,
+) {
+    for mut transform in capsules_query.iter_mut() {
+        transform.translation.y -= tuning.capsule_speed;
+    }
+}
+
+/// Hands capsules touching the paddle over to the player, and drops the ones
+/// that fell past it.
+fn collect_capsules(
+    mut commands: Commands,
+    capsules_query: Query<(Entity, &Capsule, &Transform)>,
+    player_query: Query<(&Player, &Transform)>,
+    mut active: ResMut<ActivePowerUp>,
+    mut power_up_collected_event: EventWriter<PowerUpCollected>,
+    arena: Res<Arena>,
+) {
+    let (player, player_transform) = player_query.single();
+
+    for (entity, capsule, transform) in capsules_query.iter() {
+        let caught = collide(
+            transform.translation,
+            CAPSULE_SIZE,
+            player_transform.translation,
+            player.get_size(),
+        )
+        .is_some();
+
+        if caught {
+            active.0 = Some(capsule.kind);
+            power_up_collected_event.send(PowerUpCollected { kind: capsule.kind });
+            commands.entity(entity).despawn();
+        } else if transform.translation.y < -arena.half_size().y {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn reset(
+    mut commands: Commands,
+    capsules_query: Query<Entity, With<Capsule>>,
+    mut active: ResMut<ActivePowerUp>,
+) {
+    for entity in capsules_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    active.0 = None;
+}
@@ -0,0 +1,571 @@
+//! Recording and playback of runs.
+//!
+//! Replay files are little endian binary:
+//!
+//! | Field          | Type                     | Notes                                   |
+//! |----------------|--------------------------|-----------------------------------------|
+//! | magic          | `[u8; 4]`                | `ARKR`                                  |
+//! | format version | `u16`                    | [`REPLAY_FORMAT_VERSION`]               |
+//! | game version   | `u8` length + UTF-8      | `CARGO_PKG_VERSION` of the recording    |
+//! | level id       | `u32`                    | See [`LEVELS`](super::LEVELS)           |
+//! | difficulty     | `u8`                     | Index in [`Difficulty::ALL`]            |
+//! | seed           | `u64`                    | Seed of [`GameRng`]                     |
+//! | tick rate      | `u16`                    | Gameplay ticks per second               |
+//! | tuning         | `u32`                    | [`Tuning::checksum`] of the recording   |
+//! | final score    | `i32`                    | Score when the recording ended          |
+//! | score checksum | `u32`                    | FNV-1a of seed, tick count, final score |
+//! | run count      | `u32`                    |                                         |
+//! | runs           | run count × (`u8`, `u32`) | Action bits and number of ticks         |
+//!
+//! Any mismatch with the running game is rejected by [`Replay::load`] since
+//! the simulation would silently desync otherwise. The tuning file is only
+//! loaded once the game runs, so a replay recorded with other tuning is
+//! rejected when its playback starts, by [`Replay::check_tuning`].
+
+use std::{
+    fmt, fs,
+    io::{self, Read, Write},
+    path::Path,
+};
+
+use bevy::prelude::*;
+
+use super::{
+    bytes::{fnv1a, ByteReader, Truncated},
+    CurrentLevel, Difficulty, GameRng, GameState, GameplaySet, Level, PlayerActions, RunState,
+    SeedRun, Tuning, TICKS_PER_SECOND,
+};
+
+pub const REPLAY_FORMAT_VERSION: u16 = 5;
+
+const REPLAY_MAGIC: &[u8; 4] = b"ARKR";
+const GAME_VERSION: &str = env!("CARGO_PKG_VERSION");
+const REPLAY_DIR: &str = "replays";
+const LAST_REPLAY_PATH: &str = "replays/last.replay";
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        // A replay handed over on the command line is played back as soon as
+        // the tuning file is loaded.
+        if app.world.contains_resource::<ReplayPlayback>() {
+            app.insert_resource(NextState(Some(GameState::Replaying)));
+        }
+
+        app.init_resource::<ReplayRecorder>()
+            .add_system(
+                start_recording
+                    .in_set(SeedRun)
+                    .in_schedule(OnEnter(GameState::Playing)),
+            )
+            .add_system(finish_recording.in_schedule(OnExit(GameState::Playing)))
+            .add_system(
+                start_playback
+                    .in_set(SeedRun)
+                    .in_schedule(OnEnter(GameState::Replaying)),
+            )
+            .add_system(finish_playback.in_schedule(OnExit(GameState::Replaying)))
+            .add_system(
+                stop_recording_on_tuning_change
+                    .run_if(resource_changed::<Tuning>())
+                    .in_set(OnUpdate(GameState::Playing)),
+            )
+            .add_system(
+                record_actions
+                    .run_if(in_state(GameState::Playing))
+                    .in_set(GameplaySet::Input)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            )
+            .add_system(
+                play_actions
+                    .run_if(in_state(GameState::Replaying))
+                    .in_set(GameplaySet::ReadActions)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            )
+            .add_system(process_replay_input.in_set(OnUpdate(GameState::GameOver)));
+    }
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    NotAReplay,
+    Truncated,
+    UnsupportedFormat { found: u16 },
+    GameVersionMismatch { found: String },
+    UnknownLevel(u32),
+    UnknownDifficulty(u8),
+    TickRateMismatch { found: u16 },
+    TuningMismatch,
+    InvalidActions(u8),
+    ChecksumMismatch,
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Io(err) => write!(f, "{}", err),
+            ReplayError::NotAReplay => write!(f, "not a replay file"),
+            ReplayError::Truncated => write!(f, "replay file is truncated"),
+            ReplayError::UnsupportedFormat { found } => write!(
+                f,
+                "replay format version {} is not supported (expected {})",
+                found, REPLAY_FORMAT_VERSION
+            ),
+            ReplayError::GameVersionMismatch { found } => write!(
+                f,
+                "replay was recorded with game version {} but this is {}",
+                found, GAME_VERSION
+            ),
+            ReplayError::UnknownLevel(id) => write!(f, "replay uses unknown level {}", id),
+            ReplayError::UnknownDifficulty(index) => {
+                write!(f, "replay uses unknown difficulty {}", index)
+            }
+            ReplayError::TickRateMismatch { found } => write!(
+                f,
+                "replay was recorded at {} ticks per second but the game runs at {}",
+                found, TICKS_PER_SECOND
+            ),
+            ReplayError::TuningMismatch => {
+                write!(f, "replay was recorded with different gameplay tuning")
+            }
+            ReplayError::InvalidActions(bits) => {
+                write!(f, "replay contains invalid actions {:#04x}", bits)
+            }
+            ReplayError::ChecksumMismatch => write!(f, "replay score checksum does not match"),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl From<Truncated> for ReplayError {
+    fn from(_: Truncated) -> Self {
+        ReplayError::Truncated
+    }
+}
+
+impl From<io::Error> for ReplayError {
+    fn from(err: io::Error) -> Self {
+        ReplayError::Io(err)
+    }
+}
+
+/// A recorded run: where and how it started and the player's actions for
+/// every gameplay tick, stored as runs of identical actions.
+#[derive(Clone, Default, Debug)]
+pub struct Replay {
+    pub level: u32,
+    pub difficulty: Difficulty,
+    pub seed: u64,
+    /// [`Tuning::checksum`] of the tuning the run was played with.
+    pub tuning: u32,
+    pub final_score: i32,
+    runs: Vec<(PlayerActions, u32)>,
+}
+
+impl Replay {
+    pub fn new(level: u32, difficulty: Difficulty, seed: u64, tuning: &Tuning) -> Self {
+        Self {
+            level,
+            difficulty,
+            seed,
+            tuning: tuning.checksum(),
+            final_score: 0,
+            runs: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, actions: PlayerActions) {
+        match self.runs.last_mut() {
+            Some((last, count)) if *last == actions && *count < u32::MAX => *count += 1,
+            _ => self.runs.push((actions, 1)),
+        }
+    }
+
+    pub fn ticks(&self) -> u64 {
+        self.runs.iter().map(|(_, count)| *count as u64).sum()
+    }
+
+    fn score_checksum(&self) -> u32 {
+        fnv1a(
+            self.seed
+                .to_le_bytes()
+                .into_iter()
+                .chain(self.ticks().to_le_bytes())
+                .chain(self.final_score.to_le_bytes()),
+        )
+    }
+
+    /// Fails unless the replay was recorded with `tuning`, it would desync
+    /// otherwise.
+    pub fn check_tuning(&self, tuning: &Tuning) -> Result<(), ReplayError> {
+        if self.tuning != tuning.checksum() {
+            return Err(ReplayError::TuningMismatch);
+        }
+
+        Ok(())
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(REPLAY_MAGIC);
+        bytes.extend_from_slice(&REPLAY_FORMAT_VERSION.to_le_bytes());
+        bytes.push(GAME_VERSION.len() as u8);
+        bytes.extend_from_slice(GAME_VERSION.as_bytes());
+        bytes.extend_from_slice(&self.level.to_le_bytes());
+        bytes.push(self.difficulty.to_index());
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.extend_from_slice(&(TICKS_PER_SECOND as u16).to_le_bytes());
+        bytes.extend_from_slice(&self.tuning.to_le_bytes());
+        bytes.extend_from_slice(&self.final_score.to_le_bytes());
+        bytes.extend_from_slice(&self.score_checksum().to_le_bytes());
+        bytes.extend_from_slice(&(self.runs.len() as u32).to_le_bytes());
+        for (actions, count) in &self.runs {
+            bytes.push(actions.to_bits());
+            bytes.extend_from_slice(&count.to_le_bytes());
+        }
+
+        fs::File::create(path)?.write_all(&bytes)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ReplayError> {
+        let mut bytes = Vec::new();
+        fs::File::open(path)?.read_to_end(&mut bytes)?;
+
+        let mut reader = ByteReader(&bytes);
+
+        if reader.take(4)? != REPLAY_MAGIC {
+            return Err(ReplayError::NotAReplay);
+        }
+
+        let format = reader.u16()?;
+        if format != REPLAY_FORMAT_VERSION {
+            return Err(ReplayError::UnsupportedFormat { found: format });
+        }
+
+        let version_len = reader.u8()? as usize;
+        let version = String::from_utf8_lossy(reader.take(version_len)?).into_owned();
+        if version != GAME_VERSION {
+            return Err(ReplayError::GameVersionMismatch { found: version });
+        }
+
+        let level = reader.u32()?;
+        if Level::get(level).is_none() {
+            return Err(ReplayError::UnknownLevel(level));
+        }
+
+        let difficulty_index = reader.u8()?;
+        let difficulty = Difficulty::from_index(difficulty_index)
+            .ok_or(ReplayError::UnknownDifficulty(difficulty_index))?;
+
+        let seed = reader.u64()?;
+
+        let tick_rate = reader.u16()?;
+        if tick_rate as u32 != TICKS_PER_SECOND {
+            return Err(ReplayError::TickRateMismatch { found: tick_rate });
+        }
+
+        let tuning = reader.u32()?;
+
+        let final_score = reader.i32()?;
+        let checksum = reader.u32()?;
+
+        let run_count = reader.u32()?;
+        let mut runs = Vec::new();
+        for _ in 0..run_count {
+            let bits = reader.u8()?;
+            let actions =
+                PlayerActions::try_from_bits(bits).ok_or(ReplayError::InvalidActions(bits))?;
+            runs.push((actions, reader.u32()?));
+        }
+
+        let replay = Self {
+            level,
+            difficulty,
+            seed,
+            tuning,
+            final_score,
+            runs,
+        };
+
+        if replay.score_checksum() != checksum {
+            return Err(ReplayError::ChecksumMismatch);
+        }
+
+        Ok(replay)
+    }
+}
+
+/// The run being recorded while playing. Empty for resumed runs, which
+/// cannot be reproduced from their seed, and for runs whose tuning changed
+/// while playing.
+#[derive(Resource, Default)]
+pub struct ReplayRecorder(pub Option<Replay>);
+
+/// The replay to watch, along with how far it has been played.
+#[derive(Resource)]
+pub struct ReplayPlayback {
+    pub replay: Replay,
+    run: usize,
+    tick_in_run: u32,
+}
+
+impl ReplayPlayback {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            replay,
+            run: 0,
+            tick_in_run: 0,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.run >= self.replay.runs.len()
+    }
+
+    fn rewind(&mut self) {
+        self.run = 0;
+        self.tick_in_run = 0;
+    }
+
+    fn next_actions(&mut self) -> Option<PlayerActions> {
+        let (actions, count) = *self.replay.runs.get(self.run)?;
+
+        self.tick_in_run += 1;
+        if self.tick_in_run >= count {
+            self.run += 1;
+            self.tick_in_run = 0;
+        }
+
+        Some(actions)
+    }
+}
+
+fn start_recording(
+    mut recorder: ResMut<ReplayRecorder>,
+    mut rng: ResMut<GameRng>,
+    current_level: Res<CurrentLevel>,
+    difficulty: Res<Difficulty>,
+    tuning: Res<Tuning>,
+) {
+    let seed = rand::random();
+
+    rng.reseed(seed);
+    recorder.0 = Some(Replay::new(current_level.0, *difficulty, seed, &tuning));
+}
+
+fn finish_recording(
+    mut commands: Commands,
+    mut recorder: ResMut<ReplayRecorder>,
+    run: Res<RunState>,
+) {
+    let Some(mut replay) = recorder.0.take() else {
+        // Whatever replay was kept belongs to an older run.
+        commands.remove_resource::<ReplayPlayback>();
+        return;
+    };
+
+    if replay.ticks() == 0 {
+        return;
+    }
+
+    replay.final_score = run.score;
+
+    match fs::create_dir_all(REPLAY_DIR).and_then(|_| replay.save(LAST_REPLAY_PATH)) {
+        Ok(()) => info!("Replay saved to {}", LAST_REPLAY_PATH),
+        Err(err) => warn!("Could not save replay to {}: {}", LAST_REPLAY_PATH, err),
+    }
+
+    commands.insert_resource(ReplayPlayback::new(replay));
+}
+
+fn start_playback(
+    mut commands: Commands,
+    mut playback: ResMut<ReplayPlayback>,
+    mut rng: ResMut<GameRng>,
+    mut current_level: ResMut<CurrentLevel>,
+    mut difficulty: ResMut<Difficulty>,
+    mut next_state: ResMut<NextState<GameState>>,
+    tuning: Res<Tuning>,
+) {
+    if let Err(err) = playback.replay.check_tuning(&tuning) {
+        // Leaving right away, no tick of the replay is simulated.
+        warn!("Could not play replay: {}", err);
+        commands.remove_resource::<ReplayPlayback>();
+        next_state.set(GameState::MainMenu);
+        return;
+    }
+
+    playback.rewind();
+    rng.reseed(playback.replay.seed);
+    current_level.0 = playback.replay.level;
+    *difficulty = playback.replay.difficulty;
+}
+
+fn finish_playback(playback: Option<Res<ReplayPlayback>>, run: Res<RunState>) {
+    // Rejected by `start_playback`.
+    let Some(playback) = playback else {
+        return;
+    };
+
+    let score = run.score;
+
+    if playback.is_finished() && score != playback.replay.final_score {
+        warn!(
+            "Replay desynced: ended with score {} but was recorded with {}",
+            score, playback.replay.final_score
+        );
+    }
+}
+
+/// Drops the recording once the tuning changes under the run, playing it
+/// back with the tuning it started with would desync.
+fn stop_recording_on_tuning_change(mut recorder: ResMut<ReplayRecorder>, tuning: Res<Tuning>) {
+    let changed = recorder
+        .0
+        .as_ref()
+        .is_some_and(|replay| replay.check_tuning(&tuning).is_err());
+
+    if changed {
+        info!("Gameplay tuning changed, this run will not be recorded");
+        recorder.0 = None;
+    }
+}
+
+fn record_actions(mut recorder: ResMut<ReplayRecorder>, actions: Res<PlayerActions>) {
+    if let Some(replay) = recorder.0.as_mut() {
+        replay.push(*actions);
+    }
+}
+
+fn play_actions(
+    mut playback: ResMut<ReplayPlayback>,
+    mut actions: ResMut<PlayerActions>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    match playback.next_actions() {
+        Some(next) => *actions = next,
+        None => next_state.set(GameState::GameOver),
+    }
+}
+
+fn process_replay_input(
+    mut next_state: ResMut<NextState<GameState>>,
+    keyboard_input: Res<Input<KeyCode>>,
+    playback: Option<Res<ReplayPlayback>>,
+) {
+    if playback.is_some() && keyboard_input.just_pressed(KeyCode::R) {
+        next_state.set(GameState::Replaying);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "arkanoid-replay-{}-{}.replay",
+            std::process::id(),
+            name
+        ))
+    }
+
+    fn sample_replay() -> Replay {
+        let mut replay = Replay::new(1, Difficulty::Hard, 42, &Tuning::default());
+        let left = PlayerActions {
+            left: true,
+            ..default()
+        };
+        for actions in [left, left, PlayerActions::default(), left] {
+            replay.push(actions);
+        }
+        replay.final_score = 1234;
+        replay
+    }
+
+    /// Saves `replay`, lets `corrupt` change the bytes and loads them back.
+    fn reload(
+        name: &str,
+        replay: &Replay,
+        corrupt: impl FnOnce(&mut Vec<u8>),
+    ) -> Result<Replay, ReplayError> {
+        let path = temp_path(name);
+        replay.save(&path).unwrap();
+        let mut bytes = fs::read(&path).unwrap();
+        corrupt(&mut bytes);
+        fs::write(&path, bytes).unwrap();
+
+        let loaded = Replay::load(&path);
+        fs::remove_file(&path).unwrap();
+        loaded
+    }
+
+    #[test]
+    fn round_trip() {
+        let replay = sample_replay();
+        let loaded = reload("round-trip", &replay, |_| ()).unwrap();
+
+        assert_eq!(loaded.level, replay.level);
+        assert_eq!(loaded.difficulty, replay.difficulty);
+        assert_eq!(loaded.seed, replay.seed);
+        assert_eq!(loaded.tuning, replay.tuning);
+        assert_eq!(loaded.final_score, replay.final_score);
+        assert_eq!(loaded.runs, replay.runs);
+        assert_eq!(loaded.ticks(), 4);
+        assert!(loaded.check_tuning(&Tuning::default()).is_ok());
+    }
+
+    #[test]
+    fn rejects_corrupt_checksum() {
+        let loaded = reload("checksum", &sample_replay(), |bytes| {
+            // Last byte of the count of the last run of actions.
+            *bytes.last_mut().unwrap() ^= 1;
+        });
+
+        assert!(matches!(loaded, Err(ReplayError::ChecksumMismatch)));
+    }
+
+    #[test]
+    fn rejects_other_format_version() {
+        let loaded = reload("version", &sample_replay(), |bytes| {
+            bytes[4..6].copy_from_slice(&(REPLAY_FORMAT_VERSION + 1).to_le_bytes());
+        });
+
+        assert!(matches!(
+            loaded,
+            Err(ReplayError::UnsupportedFormat { found }) if found == REPLAY_FORMAT_VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn rejects_truncated_header() {
+        let loaded = reload("truncated", &sample_replay(), |bytes| bytes.truncate(10));
+
+        assert!(matches!(loaded, Err(ReplayError::Truncated)));
+    }
+
+    #[test]
+    fn rejects_other_file() {
+        let loaded = reload("magic", &sample_replay(), |bytes| bytes[0] = b'X');
+
+        assert!(matches!(loaded, Err(ReplayError::NotAReplay)));
+    }
+
+    #[test]
+    fn rejects_other_tuning() {
+        let tuning = Tuning {
+            ball_damage: 50.,
+            ..default()
+        };
+
+        assert!(matches!(
+            sample_replay().check_tuning(&tuning),
+            Err(ReplayError::TuningMismatch)
+        ));
+    }
+}
@@ -0,0 +1,748 @@
+//! Saving the run in progress when quitting, and resuming it from the main
+//! menu.
+//!
+//! Save files are little endian binary:
+//!
+//! | Field          | Type                                | Notes                            |
+//! |----------------|-------------------------------------|----------------------------------|
+//! | magic          | `[u8; 4]`                           | `ARKS`                           |
+//! | format version | `u16`                               | [`SAVE_FORMAT_VERSION`]          |
+//! | game version   | `u8` length + UTF-8                 | `CARGO_PKG_VERSION` of the save  |
+//! | level id       | `u32`                               | See [`LEVELS`](super::LEVELS)    |
+//! | difficulty     | `u8`                                | Index in [`Difficulty::ALL`]     |
+//! | tuning         | `u32`                               | [`Tuning::checksum`] of the run  |
+//! | rng seed       | `[u8; 32]`                          | State of [`GameRng`]             |
+//! | rng stream     | `u64`                               |                                  |
+//! | rng position   | `u128`                              | Words consumed so far            |
+//! | score          | `i32`                               |                                  |
+//! | lives          | `i32`                               |                                  |
+//! | combo          | `u32`                               |                                  |
+//! | multiplier     | `i32`                               |                                  |
+//! | elapsed ticks  | `u64`                               |                                  |
+//! | level ticks    | `u64`                               |                                  |
+//! | bricks broken  | `u32`                               |                                  |
+//! | ball position  | 2 × `f32`                           |                                  |
+//! | ball speed     | `f32`                               |                                  |
+//! | ball direction | 2 × `i8`                            | `-1` or `1` on each axis         |
+//! | ball speed-up  | `u64`, `u32`, 2 × `u8`              | See [`BallSpeedUp`]              |
+//! | ball caught    | `u8`, `f32`, `u32`                  | Flag, then [`CaughtBall`] fields |
+//! | paddle x       | `f32`                               |                                  |
+//! | power-up       | `u8`                                | `0` or [`PowerUpKind`] index + 1 |
+//! | warp gate open | `u8`                                | `0` or `1`                       |
+//! | boss health    | `u8`, `f32`                         | Flag, then health left           |
+//! | brick count    | `u32`                               |                                  |
+//! | bricks         | brick count × (`u16`, `u16`, `f32`) | Row, column and health           |
+//!
+//! Falling power-up capsules, laser bolts, enemies and boss projectiles are
+//! not saved, a resumed run loses them. Moving bricks are not saved either,
+//! their place follows from the level ticks. Saves that do not match the
+//! running game are ignored with a warning. The tuning file is only loaded
+//! once the game runs, so a save made with other tuning is ignored when it
+//! is picked to resume.
+
+use std::{
+    fmt, fs,
+    io::{self, Read, Write},
+    path::Path,
+};
+
+use bevy::{app::AppExit, ecs::system::SystemParam, prelude::*};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use super::{
+    bytes::{ByteReader, Truncated},
+    tuning_ready, ActivePowerUp, Ball, BallSpeedUp, Boss, Brick, BrickKind, CaughtBall,
+    ColliderGrid, ComboChanged, Difficulty, GameRng, GameState, GameplaySet, Level, LivesChanged,
+    Player, PowerUpKind, ReplayRecorder, RunState, ScoreChanged, Tuning, WarpGate,
+};
+
+pub const SAVE_FORMAT_VERSION: u16 = 7;
+
+const SAVE_MAGIC: &[u8; 4] = b"ARKS";
+const GAME_VERSION: &str = env!("CARGO_PKG_VERSION");
+const SAVE_DIR: &str = "saves";
+const SAVE_PATH: &str = "saves/run.save";
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SavedRun(load_saved_run()))
+            .add_system(
+                process_resume_input
+                    .run_if(tuning_ready)
+                    .in_set(OnUpdate(GameState::MainMenu)),
+            )
+            .add_system(quit_to_menu.in_set(OnUpdate(GameState::Playing)))
+            .add_system(save_on_exit.in_base_set(CoreSet::Last))
+            .add_systems(
+                (
+                    restore_run.run_if(
+                        in_state(GameState::Playing).and_then(resource_exists::<ResumeRun>()),
+                    ),
+                    // Bricks gone in the saved run must not be hit on this tick.
+                    apply_system_buffers,
+                )
+                    .chain()
+                    .before(GameplaySet::ReadActions)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            );
+    }
+}
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(io::Error),
+    NotASave,
+    Truncated,
+    UnsupportedFormat { found: u16 },
+    GameVersionMismatch { found: String },
+    UnknownLevel(u32),
+    UnknownDifficulty(u8),
+    TuningMismatch,
+    InvalidBall,
+    UnknownPowerUp(u8),
+    InvalidBoss,
+    UnknownBrick { row: u16, column: u16 },
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::Io(err) => write!(f, "{}", err),
+            SaveError::NotASave => write!(f, "not a save file"),
+            SaveError::Truncated => write!(f, "save file is truncated"),
+            SaveError::UnsupportedFormat { found } => write!(
+                f,
+                "save format version {} is not supported (expected {})",
+                found, SAVE_FORMAT_VERSION
+            ),
+            SaveError::GameVersionMismatch { found } => write!(
+                f,
+                "save was made with game version {} but this is {}",
+                found, GAME_VERSION
+            ),
+            SaveError::UnknownLevel(id) => write!(f, "save uses unknown level {}", id),
+            SaveError::UnknownDifficulty(index) => {
+                write!(f, "save uses unknown difficulty {}", index)
+            }
+            SaveError::TuningMismatch => write!(f, "save was made with different gameplay tuning"),
+            SaveError::InvalidBall => write!(f, "save contains an invalid ball"),
+            SaveError::UnknownPowerUp(index) => write!(f, "save uses unknown power-up {}", index),
+            SaveError::InvalidBoss => write!(f, "save contains an invalid boss"),
+            SaveError::UnknownBrick { row, column } => write!(
+                f,
+                "save has a brick at row {} column {} where the level has none",
+                row, column
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SaveError {}
+
+impl From<Truncated> for SaveError {
+    fn from(_: Truncated) -> Self {
+        SaveError::Truncated
+    }
+}
+
+impl From<io::Error> for SaveError {
+    fn from(err: io::Error) -> Self {
+        SaveError::Io(err)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SavedBrick {
+    /// Row and column of the brick in the level layout.
+    pub cell: (usize, usize),
+    pub health: f32,
+}
+
+/// Everything needed to pick a run up exactly where it was left.
+#[derive(Clone, Debug)]
+pub struct RunSave {
+    pub run: RunState,
+    /// [`Tuning::checksum`] of the tuning the run was played with.
+    pub tuning: u32,
+    pub rng: ChaCha8Rng,
+    pub ball_position: Vec2,
+    pub ball_speed: f32,
+    pub ball_direction: (i32, i32),
+    pub ball_speed_up: BallSpeedUp,
+    pub ball_caught: Option<CaughtBall>,
+    pub paddle_x: f32,
+    pub power_up: Option<PowerUpKind>,
+    pub warp_gate_open: bool,
+    /// Health left to the boss of the level, if it has one.
+    pub boss_health: Option<f32>,
+    pub bricks: Vec<SavedBrick>,
+}
+
+impl RunSave {
+    /// Fails unless the run was played with `tuning`, it would not play on
+    /// the same otherwise.
+    pub fn check_tuning(&self, tuning: &Tuning) -> Result<(), SaveError> {
+        if self.tuning != tuning.checksum() {
+            return Err(SaveError::TuningMismatch);
+        }
+
+        Ok(())
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(SAVE_MAGIC);
+        bytes.extend_from_slice(&SAVE_FORMAT_VERSION.to_le_bytes());
+        bytes.push(GAME_VERSION.len() as u8);
+        bytes.extend_from_slice(GAME_VERSION.as_bytes());
+        bytes.extend_from_slice(&self.run.level.to_le_bytes());
+        bytes.push(self.run.difficulty.to_index());
+        bytes.extend_from_slice(&self.tuning.to_le_bytes());
+        bytes.extend_from_slice(&self.rng.get_seed());
+        bytes.extend_from_slice(&self.rng.get_stream().to_le_bytes());
+        bytes.extend_from_slice(&self.rng.get_word_pos().to_le_bytes());
+        bytes.extend_from_slice(&self.run.score.to_le_bytes());
+        bytes.extend_from_slice(&self.run.lives.to_le_bytes());
+        bytes.extend_from_slice(&self.run.combo.to_le_bytes());
+        bytes.extend_from_slice(&self.run.multiplier.to_le_bytes());
+        bytes.extend_from_slice(&self.run.elapsed_ticks.to_le_bytes());
+        bytes.extend_from_slice(&self.run.level_ticks.to_le_bytes());
+        bytes.extend_from_slice(&self.run.bricks_broken.to_le_bytes());
+        bytes.extend_from_slice(&self.ball_position.x.to_le_bytes());
+        bytes.extend_from_slice(&self.ball_position.y.to_le_bytes());
+        bytes.extend_from_slice(&self.ball_speed.to_le_bytes());
+        bytes.push(self.ball_direction.0 as i8 as u8);
+        bytes.push(self.ball_direction.1 as i8 as u8);
+        bytes.extend_from_slice(&self.ball_speed_up.ticks.to_le_bytes());
+        bytes.extend_from_slice(&self.ball_speed_up.paddle_hits.to_le_bytes());
+        bytes.push(self.ball_speed_up.hit_top_wall as u8);
+        bytes.push(self.ball_speed_up.hit_back_rows as u8);
+        let caught = self.ball_caught.unwrap_or(CaughtBall {
+            offset: 0.,
+            ticks_left: 0,
+        });
+        bytes.push(self.ball_caught.is_some() as u8);
+        bytes.extend_from_slice(&caught.offset.to_le_bytes());
+        bytes.extend_from_slice(&caught.ticks_left.to_le_bytes());
+        bytes.extend_from_slice(&self.paddle_x.to_le_bytes());
+        bytes.push(self.power_up.map_or(0, |kind| kind.to_index() + 1));
+        bytes.push(self.warp_gate_open as u8);
+        bytes.push(self.boss_health.is_some() as u8);
+        bytes.extend_from_slice(&self.boss_health.unwrap_or(0.).to_le_bytes());
+        bytes.extend_from_slice(&(self.bricks.len() as u32).to_le_bytes());
+        for brick in &self.bricks {
+            bytes.extend_from_slice(&(brick.cell.0 as u16).to_le_bytes());
+            bytes.extend_from_slice(&(brick.cell.1 as u16).to_le_bytes());
+            bytes.extend_from_slice(&brick.health.to_le_bytes());
+        }
+
+        fs::File::create(path)?.write_all(&bytes)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SaveError> {
+        let mut bytes = Vec::new();
+        fs::File::open(path)?.read_to_end(&mut bytes)?;
+
+        let mut reader = ByteReader(&bytes);
+
+        if reader.take(4)? != SAVE_MAGIC {
+            return Err(SaveError::NotASave);
+        }
+
+        let format = reader.u16()?;
+        if format != SAVE_FORMAT_VERSION {
+            return Err(SaveError::UnsupportedFormat { found: format });
+        }
+
+        let version_len = reader.u8()? as usize;
+        let version = String::from_utf8_lossy(reader.take(version_len)?).into_owned();
+        if version != GAME_VERSION {
+            return Err(SaveError::GameVersionMismatch { found: version });
+        }
+
+        let level_id = reader.u32()?;
+        let level = Level::get(level_id).ok_or(SaveError::UnknownLevel(level_id))?;
+
+        let difficulty_index = reader.u8()?;
+        let difficulty = Difficulty::from_index(difficulty_index)
+            .ok_or(SaveError::UnknownDifficulty(difficulty_index))?;
+
+        let tuning = reader.u32()?;
+
+        let mut rng = ChaCha8Rng::from_seed(reader.take(32)?.try_into().unwrap());
+        rng.set_stream(reader.u64()?);
+        rng.set_word_pos(reader.u128()?);
+
+        let score = reader.i32()?;
+        let lives = reader.i32()?;
+        let run = RunState {
+            score,
+            combo: reader.u32()?,
+            multiplier: reader.i32()?,
+            elapsed_ticks: reader.u64()?,
+            level_ticks: reader.u64()?,
+            bricks_broken: reader.u32()?,
+            ..RunState::new(level_id, difficulty, lives)
+        };
+
+        let ball_position = Vec2::new(reader.f32()?, reader.f32()?);
+        let ball_speed = reader.f32()?;
+        let ball_direction = (reader.u8()? as i8 as i32, reader.u8()? as i8 as i32);
+        let ball_speed_up = BallSpeedUp {
+            ticks: reader.u64()?,
+            paddle_hits: reader.u32()?,
+            hit_top_wall: reader.u8()? != 0,
+            hit_back_rows: reader.u8()? != 0,
+        };
+        let is_caught = reader.u8()? != 0;
+        let caught = CaughtBall {
+            offset: reader.f32()?,
+            ticks_left: reader.u32()?,
+        };
+        let ball_caught = is_caught.then_some(caught);
+        let paddle_x = reader.f32()?;
+        let power_up = match reader.u8()? {
+            0 => None,
+            index => {
+                Some(PowerUpKind::from_index(index - 1).ok_or(SaveError::UnknownPowerUp(index))?)
+            }
+        };
+        let warp_gate_open = reader.u8()? != 0;
+        let has_boss = reader.u8()? != 0;
+        let health = reader.f32()?;
+        if has_boss != level.boss.is_some() || !health.is_finite() {
+            return Err(SaveError::InvalidBoss);
+        }
+        let boss_health = has_boss.then_some(health);
+
+        let is_unit = |value: i32| value == 1 || value == -1;
+        if !ball_position.is_finite()
+            || !ball_speed.is_finite()
+            || !is_unit(ball_direction.0)
+            || !is_unit(ball_direction.1)
+            || !paddle_x.is_finite()
+            || !caught.offset.is_finite()
+        {
+            return Err(SaveError::InvalidBall);
+        }
+
+        let brick_count = reader.u32()?;
+        let mut bricks = Vec::new();
+        for _ in 0..brick_count {
+            let (row, column) = (reader.u16()?, reader.u16()?);
+            let health = reader.f32()?;
+
+            let in_layout = level
+                .layout
+                .get(row as usize)
+                .and_then(|cells| cells.chars().nth(column as usize))
+                .and_then(BrickKind::from_layout_char)
+                .is_some();
+            if !in_layout || !health.is_finite() {
+                return Err(SaveError::UnknownBrick { row, column });
+            }
+
+            bricks.push(SavedBrick {
+                cell: (row as usize, column as usize),
+                health,
+            });
+        }
+
+        Ok(Self {
+            run,
+            tuning,
+            rng,
+            ball_position,
+            ball_speed,
+            ball_direction,
+            ball_speed_up,
+            ball_caught,
+            paddle_x,
+            power_up,
+            warp_gate_open,
+            boss_health,
+            bricks,
+        })
+    }
+}
+
+/// The run saved by the last session, offered on the main menu.
+#[derive(Resource)]
+pub struct SavedRun(pub Option<RunSave>);
+
+/// The run to restore on the first tick after entering
+/// [`GameState::Playing`].
+#[derive(Resource)]
+pub struct ResumeRun(pub RunSave);
+
+/// The state a [`RunSave`] is captured from.
+#[derive(SystemParam)]
+pub struct RunSnapshot<'w, 's> {
+    run: Res<'w, RunState>,
+    tuning: Res<'w, Tuning>,
+    rng: Res<'w, GameRng>,
+    ball_query: Query<'w, 's, (&'static Ball, &'static Transform)>,
+    player_query: Query<'w, 's, &'static Transform, With<Player>>,
+    bricks_query: Query<'w, 's, &'static Brick>,
+    gates_query: Query<'w, 's, &'static WarpGate>,
+    power_up: Res<'w, ActivePowerUp>,
+    boss_query: Query<'w, 's, &'static Boss>,
+}
+
+impl<'w, 's> RunSnapshot<'w, 's> {
+    /// Returns `None` once the run is over, there is nothing left to resume.
+    pub fn capture(&self) -> Option<RunSave> {
+        if self.run.lives <= 0 || self.run.cleared {
+            return None;
+        }
+
+        let (ball, ball_transform) = self.ball_query.get_single().ok()?;
+        let player_transform = self.player_query.get_single().ok()?;
+
+        Some(RunSave {
+            run: self.run.clone(),
+            tuning: self.tuning.checksum(),
+            rng: self.rng.0.clone(),
+            ball_position: ball_transform.translation.truncate(),
+            ball_speed: ball.speed,
+            ball_direction: ball.direction,
+            ball_speed_up: ball.speed_up,
+            ball_caught: ball.caught,
+            paddle_x: player_transform.translation.x,
+            power_up: self.power_up.0,
+            warp_gate_open: self.gates_query.iter().any(|gate| gate.open),
+            boss_health: self.boss_query.get_single().ok().map(|boss| boss.health),
+            bricks: self
+                .bricks_query
+                .iter()
+                .filter(|brick| brick.health() > 0.)
+                .map(|brick| SavedBrick {
+                    cell: brick.cell,
+                    health: brick.health(),
+                })
+                .collect(),
+        })
+    }
+}
+
+fn load_saved_run() -> Option<RunSave> {
+    if !Path::new(SAVE_PATH).exists() {
+        return None;
+    }
+
+    match RunSave::load(SAVE_PATH) {
+        Ok(save) => Some(save),
+        Err(err) => {
+            warn!("Ignoring saved run {}: {}", SAVE_PATH, err);
+            None
+        }
+    }
+}
+
+fn write_save(save: &RunSave) {
+    match fs::create_dir_all(SAVE_DIR).and_then(|_| save.save(SAVE_PATH)) {
+        Ok(()) => info!("Run saved to {}", SAVE_PATH),
+        Err(err) => warn!("Could not save run to {}: {}", SAVE_PATH, err),
+    }
+}
+
+fn quit_to_menu(
+    mut next_state: ResMut<NextState<GameState>>,
+    keyboard_input: Res<Input<KeyCode>>,
+    snapshot: RunSnapshot,
+    mut saved_run: ResMut<SavedRun>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::Escape) {
+        return;
+    }
+
+    if let Some(save) = snapshot.capture() {
+        write_save(&save);
+        saved_run.0 = Some(save);
+    }
+
+    next_state.set(GameState::MainMenu);
+}
+
+fn save_on_exit(
+    mut exit_events: EventReader<AppExit>,
+    state: Res<State<GameState>>,
+    snapshot: RunSnapshot,
+) {
+    if exit_events.iter().count() == 0 || state.0 != GameState::Playing {
+        return;
+    }
+
+    if let Some(save) = snapshot.capture() {
+        write_save(&save);
+    }
+}
+
+fn process_resume_input(
+    mut commands: Commands,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut saved_run: ResMut<SavedRun>,
+    keyboard_input: Res<Input<KeyCode>>,
+    tuning: Res<Tuning>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::C) {
+        return;
+    }
+
+    let Some(save) = saved_run.0.take() else {
+        return;
+    };
+
+    // Kept on disk, it can be resumed again with the tuning it was made with.
+    if let Err(err) = save.check_tuning(&tuning) {
+        warn!("Ignoring saved run {}: {}", SAVE_PATH, err);
+        return;
+    }
+
+    // A run can only be resumed once.
+    if let Err(err) = fs::remove_file(SAVE_PATH) {
+        warn!("Could not remove saved run {}: {}", SAVE_PATH, err);
+    }
+
+    commands.insert_resource(ResumeRun(save));
+    next_state.set(GameState::Playing);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn restore_run(
+    mut commands: Commands,
+    resume: Res<ResumeRun>,
+    mut run: ResMut<RunState>,
+    mut rng: ResMut<GameRng>,
+    mut recorder: ResMut<ReplayRecorder>,
+    mut ball_query: Query<(&mut Ball, &mut Transform), Without<Player>>,
+    mut player_query: Query<&mut Transform, (With<Player>, Without<Ball>)>,
+    mut bricks_query: Query<&mut Brick>,
+    mut gates_query: Query<(&mut WarpGate, &mut Visibility)>,
+    mut boss_query: Query<&mut Boss>,
+    mut collider_grid: ResMut<ColliderGrid>,
+    mut power_up: ResMut<ActivePowerUp>,
+    mut score_changed_event: EventWriter<ScoreChanged>,
+    mut lives_changed_event: EventWriter<LivesChanged>,
+    mut combo_changed_event: EventWriter<ComboChanged>,
+) {
+    let save = &resume.0;
+
+    let previous = std::mem::replace(run.as_mut(), save.run.clone());
+    rng.0 = save.rng.clone();
+    // The seed no longer describes the run, it could not be replayed.
+    recorder.0 = None;
+
+    let (mut ball, mut ball_transform) = ball_query.single_mut();
+    ball.speed = save.ball_speed;
+    ball.direction = save.ball_direction;
+    ball.speed_up = save.ball_speed_up;
+    ball.caught = save.ball_caught;
+    ball_transform.translation = save.ball_position.extend(ball_transform.translation.z);
+
+    player_query.single_mut().translation.x = save.paddle_x;
+
+    power_up.0 = save.power_up;
+    for (mut gate, mut visibility) in gates_query.iter_mut() {
+        gate.open = save.warp_gate_open;
+        *visibility = if gate.open {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+    }
+
+    if let (Some(health), Ok(mut boss)) = (save.boss_health, boss_query.get_single_mut()) {
+        boss.health = health;
+    }
+
+    for mut brick in bricks_query.iter_mut() {
+        match save.bricks.iter().find(|saved| saved.cell == brick.cell) {
+            Some(saved) => brick.set_health(saved.health),
+            None => {
+                commands.entity(brick.entity).despawn_recursive();
+                collider_grid.remove(brick.entity);
+            }
+        }
+    }
+
+    score_changed_event.send(ScoreChanged {
+        delta: run.score - previous.score,
+        total: run.score,
+    });
+    lives_changed_event.send(LivesChanged {
+        delta: run.lives - previous.lives,
+        total: run.lives,
+    });
+    combo_changed_event.send(ComboChanged {
+        combo: run.combo,
+        multiplier: run.multiplier,
+    });
+
+    commands.remove_resource::<ResumeRun>();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "arkanoid-save-{}-{}.save",
+            std::process::id(),
+            name
+        ))
+    }
+
+    fn sample_save() -> RunSave {
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+        rng.set_word_pos(100);
+
+        RunSave {
+            run: RunState {
+                score: 4200,
+                combo: 3,
+                multiplier: 2,
+                elapsed_ticks: 900,
+                level_ticks: 600,
+                bricks_broken: 12,
+                ..RunState::new(1, Difficulty::Easy, 7)
+            },
+            tuning: Tuning::default().checksum(),
+            rng,
+            ball_position: Vec2::new(12.5, -40.),
+            ball_speed: 3.2,
+            ball_direction: (-1, 1),
+            ball_speed_up: BallSpeedUp {
+                ticks: 300,
+                paddle_hits: 4,
+                hit_top_wall: true,
+                hit_back_rows: false,
+            },
+            ball_caught: Some(CaughtBall {
+                offset: 8.,
+                ticks_left: 20,
+            }),
+            paddle_x: -100.,
+            power_up: Some(PowerUpKind::from_index(0).unwrap()),
+            warp_gate_open: false,
+            boss_health: None,
+            bricks: vec![
+                SavedBrick {
+                    cell: (0, 0),
+                    health: 100.,
+                },
+                SavedBrick {
+                    cell: (2, 5),
+                    health: 50.,
+                },
+            ],
+        }
+    }
+
+    /// Saves `save`, lets `corrupt` change the bytes and loads them back.
+    fn reload(
+        name: &str,
+        save: &RunSave,
+        corrupt: impl FnOnce(&mut Vec<u8>),
+    ) -> Result<RunSave, SaveError> {
+        let path = temp_path(name);
+        save.save(&path).unwrap();
+        let mut bytes = fs::read(&path).unwrap();
+        corrupt(&mut bytes);
+        fs::write(&path, bytes).unwrap();
+
+        let loaded = RunSave::load(&path);
+        fs::remove_file(&path).unwrap();
+        loaded
+    }
+
+    #[test]
+    fn round_trip() {
+        let save = sample_save();
+        let loaded = reload("round-trip", &save, |_| ()).unwrap();
+
+        assert_eq!(loaded.run, save.run);
+        assert_eq!(loaded.tuning, save.tuning);
+        assert_eq!(loaded.rng, save.rng);
+        assert_eq!(loaded.ball_position, save.ball_position);
+        assert_eq!(loaded.ball_speed, save.ball_speed);
+        assert_eq!(loaded.ball_direction, save.ball_direction);
+        assert_eq!(loaded.ball_speed_up, save.ball_speed_up);
+        assert_eq!(loaded.ball_caught, save.ball_caught);
+        assert_eq!(loaded.paddle_x, save.paddle_x);
+        assert_eq!(loaded.power_up, save.power_up);
+        assert_eq!(loaded.warp_gate_open, save.warp_gate_open);
+        assert_eq!(loaded.boss_health, save.boss_health);
+        let cells: Vec<_> = loaded
+            .bricks
+            .iter()
+            .map(|brick| (brick.cell, brick.health))
+            .collect();
+        assert_eq!(cells, vec![((0, 0), 100.), ((2, 5), 50.)]);
+        assert!(loaded.check_tuning(&Tuning::default()).is_ok());
+    }
+
+    #[test]
+    fn rejects_other_format_version() {
+        let loaded = reload("version", &sample_save(), |bytes| {
+            bytes[4..6].copy_from_slice(&(SAVE_FORMAT_VERSION + 1).to_le_bytes());
+        });
+
+        assert!(matches!(
+            loaded,
+            Err(SaveError::UnsupportedFormat { found }) if found == SAVE_FORMAT_VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn rejects_truncated_header() {
+        let loaded = reload("truncated", &sample_save(), |bytes| bytes.truncate(10));
+
+        assert!(matches!(loaded, Err(SaveError::Truncated)));
+    }
+
+    #[test]
+    fn rejects_other_file() {
+        let loaded = reload("magic", &sample_save(), |bytes| bytes[0] = b'X');
+
+        assert!(matches!(loaded, Err(SaveError::NotASave)));
+    }
+
+    #[test]
+    fn rejects_brick_outside_the_layout() {
+        let mut save = sample_save();
+        save.bricks.push(SavedBrick {
+            cell: (200, 0),
+            health: 100.,
+        });
+
+        let loaded = reload("brick", &save, |_| ());
+
+        assert!(matches!(
+            loaded,
+            Err(SaveError::UnknownBrick {
+                row: 200,
+                column: 0
+            })
+        ));
+    }
+
+    #[test]
+    fn rejects_other_tuning() {
+        let tuning = Tuning {
+            ball_damage: 50.,
+            ..default()
+        };
+
+        assert!(matches!(
+            sample_save().check_tuning(&tuning),
+            Err(SaveError::TuningMismatch)
+        ));
+    }
+}
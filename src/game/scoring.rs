@@ -0,0 +1,155 @@
+use bevy::prelude::*;
+
+use super::{
+    bricks_health_check, BallLost, BossDefeated, BrickDestroyed, BrickHit, ComboChanged,
+    EnemyDestroyed, GameplaySet, Level, LevelCleared, PaddleHit, PaddleWarped, RunState,
+    ScoreChanged, Tuning,
+};
+
+pub struct ScoringPlugin;
+
+impl Plugin for ScoringPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            (
+                update_combo,
+                score_bricks,
+                score_enemies,
+                award_boss_bonus,
+                award_clear_bonus,
+                award_warp_bonus,
+            )
+                .chain()
+                .after(bricks_health_check)
+                .in_set(GameplaySet::Cleanup)
+                .in_schedule(CoreSchedule::FixedUpdate),
+        );
+    }
+}
+
+/// Grows the combo with every brick hit by the ball and breaks it when the
+/// ball touches the paddle or is lost.
+fn update_combo(
+    mut run: ResMut<RunState>,
+    mut brick_hit_events: EventReader<BrickHit>,
+    mut paddle_hit_events: EventReader<PaddleHit>,
+    mut ball_lost_events: EventReader<BallLost>,
+    mut combo_changed_event: EventWriter<ComboChanged>,
+    tuning: Res<Tuning>,
+) {
+    let previous = (run.combo, run.multiplier);
+
+    if paddle_hit_events.iter().count() > 0 || ball_lost_events.iter().count() > 0 {
+        run.combo = 0;
+    }
+    run.combo += brick_hit_events.iter().filter(|hit| hit.by_ball).count() as u32;
+    run.multiplier =
+        (1 + (run.combo / tuning.hits_per_multiplier.max(1)) as i32).min(tuning.max_multiplier);
+
+    if (run.combo, run.multiplier) != previous {
+        combo_changed_event.send(ComboChanged {
+            combo: run.combo,
+            multiplier: run.multiplier,
+        });
+    }
+}
+
+fn score_bricks(
+    mut run: ResMut<RunState>,
+    mut brick_destroyed_events: EventReader<BrickDestroyed>,
+    mut score_changed_event: EventWriter<ScoreChanged>,
+) {
+    for brick in brick_destroyed_events.iter() {
+        let points = brick.kind.points() * run.multiplier;
+        run.score += points;
+
+        score_changed_event.send(ScoreChanged {
+            delta: points,
+            total: run.score,
+        });
+    }
+}
+
+fn score_enemies(
+    mut run: ResMut<RunState>,
+    mut enemy_destroyed_events: EventReader<EnemyDestroyed>,
+    mut score_changed_event: EventWriter<ScoreChanged>,
+    tuning: Res<Tuning>,
+) {
+    for _ in enemy_destroyed_events.iter() {
+        let points = tuning.enemy_points * run.multiplier;
+        run.score += points;
+
+        score_changed_event.send(ScoreChanged {
+            delta: points,
+            total: run.score,
+        });
+    }
+}
+
+fn award_boss_bonus(
+    mut run: ResMut<RunState>,
+    mut boss_defeated_events: EventReader<BossDefeated>,
+    mut score_changed_event: EventWriter<ScoreChanged>,
+    tuning: Res<Tuning>,
+) {
+    for _ in boss_defeated_events.iter() {
+        run.score += tuning.boss_points;
+        score_changed_event.send(ScoreChanged {
+            delta: tuning.boss_points,
+            total: run.score,
+        });
+    }
+}
+
+fn award_clear_bonus(
+    mut run: ResMut<RunState>,
+    mut level_cleared_events: EventReader<LevelCleared>,
+    mut score_changed_event: EventWriter<ScoreChanged>,
+    tuning: Res<Tuning>,
+) {
+    if level_cleared_events.iter().count() == 0 {
+        return;
+    }
+
+    let par_seconds = Level::get(run.level).map_or(0., |level| level.par_seconds);
+    let seconds_under_par = (par_seconds - run.level_elapsed_secs()).max(0.);
+    let fast_clear_bonus = (seconds_under_par * tuning.fast_clear_points_per_second) as i32;
+    let lives_bonus = run.lives.max(0) * tuning.life_bonus_points;
+    let points = fast_clear_bonus + lives_bonus;
+
+    info!(
+        "Level cleared in {:.1}s: fast clear bonus {}, lives bonus {}",
+        run.level_elapsed_secs(),
+        fast_clear_bonus,
+        lives_bonus
+    );
+
+    run.score += points;
+    score_changed_event.send(ScoreChanged {
+        delta: points,
+        total: run.score,
+    });
+}
+
+pub fn award_warp_bonus(
+    mut run: ResMut<RunState>,
+    mut paddle_warped_events: EventReader<PaddleWarped>,
+    mut score_changed_event: EventWriter<ScoreChanged>,
+    tuning: Res<Tuning>,
+) {
+    if paddle_warped_events.iter().count() == 0 {
+        return;
+    }
+
+    info!(
+        "Warped out of the level: bonus {}",
+        tuning.warp_bonus_points
+    );
+
+    run.score += tuning.warp_bonus_points;
+    score_changed_event.send(ScoreChanged {
+        delta: tuning.warp_bonus_points,
+        total: run.score,
+    });
+}
@@ -0,0 +1,195 @@
+//! Player settings, kept in `settings.ron` in the user config directory.
+//!
+//! Missing or invalid settings fall back to the defaults. A valid file is
+//! rewritten at startup so it always lists every option, an invalid one is
+//! left alone until a setting changes so a typo does not wipe it.
+
+use std::{fs, path::PathBuf};
+
+use bevy::{
+    prelude::*,
+    window::{PresentMode, PrimaryWindow, WindowMode},
+};
+use serde::{Deserialize, Serialize};
+
+use super::{CurrentLevel, Difficulty, EffectSettings, VolumeSettings};
+
+const SETTINGS_FILE: &str = "settings.ron";
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(apply_settings)
+            .add_system(save_settings.run_if(resource_changed::<Settings>()));
+    }
+}
+
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    pub display: DisplaySettings,
+    pub audio: VolumeSettings,
+    pub effects: EffectSettings,
+    pub controls: ControlSettings,
+    pub gameplay: GameplaySettings,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct DisplaySettings {
+    pub fullscreen: bool,
+    pub vsync: bool,
+    /// Background colour as red, green and blue between `0.` and `1.`.
+    pub background: [f32; 3],
+    pub show_fps: bool,
+    /// Name of the theme in `assets/themes`, empty for plain shapes.
+    pub theme: String,
+}
+
+impl Default for DisplaySettings {
+    fn default() -> Self {
+        Self {
+            fullscreen: false,
+            vsync: true,
+            background: [0.1, 0.1, 0.1],
+            show_fps: true,
+            theme: "classic".to_string(),
+        }
+    }
+}
+
+impl DisplaySettings {
+    pub fn window_mode(&self) -> WindowMode {
+        if self.fullscreen {
+            WindowMode::BorderlessFullscreen
+        } else {
+            WindowMode::Windowed
+        }
+    }
+
+    pub fn present_mode(&self) -> PresentMode {
+        if self.vsync {
+            PresentMode::AutoVsync
+        } else {
+            PresentMode::AutoNoVsync
+        }
+    }
+
+    pub fn clear_color(&self) -> ClearColor {
+        let [red, green, blue] = self.background;
+        ClearColor(Color::rgb(red, green, blue))
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct ControlSettings {
+    pub left: KeyCode,
+    pub right: KeyCode,
+    pub boost: KeyCode,
+    pub fire: KeyCode,
+    pub launch: KeyCode,
+    pub toggle_autoplay: KeyCode,
+}
+
+impl Default for ControlSettings {
+    fn default() -> Self {
+        Self {
+            left: KeyCode::A,
+            right: KeyCode::D,
+            boost: KeyCode::LShift,
+            fire: KeyCode::Space,
+            launch: KeyCode::Space,
+            toggle_autoplay: KeyCode::F2,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct GameplaySettings {
+    /// Id of the level new runs start on.
+    pub starting_level: u32,
+    /// Last difficulty picked on the main menu.
+    pub difficulty: Difficulty,
+    /// Start every run with the AI driving the paddle.
+    pub autoplay: bool,
+}
+
+impl Default for GameplaySettings {
+    fn default() -> Self {
+        Self {
+            starting_level: CurrentLevel::default().0,
+            difficulty: Difficulty::default(),
+            autoplay: false,
+        }
+    }
+}
+
+impl Settings {
+    pub fn path() -> PathBuf {
+        dirs::config_dir()
+            .map(|dir| dir.join(env!("CARGO_PKG_NAME")))
+            .unwrap_or_default()
+            .join(SETTINGS_FILE)
+    }
+
+    /// Reads the settings file. A missing file, or missing options in it,
+    /// get the defaults.
+    pub fn load() -> Result<Self, ron::error::SpannedError> {
+        match fs::read_to_string(Self::path()) {
+            Ok(text) => ron::from_str(&text),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::path();
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|err| err.to_string())?;
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+        }
+
+        fs::write(&path, text).map_err(|err| err.to_string())
+    }
+}
+
+/// Marks the settings file as invalid, the settings in use are the defaults.
+#[derive(Resource)]
+pub struct InvalidSettingsFile;
+
+fn save_settings(settings: Res<Settings>, invalid_file: Option<Res<InvalidSettingsFile>>) {
+    if settings.is_added() && invalid_file.is_some() {
+        return;
+    }
+
+    if let Err(err) = settings.save() {
+        warn!(
+            "Could not save settings to {}: {}",
+            Settings::path().display(),
+            err
+        );
+    }
+}
+
+fn apply_settings(
+    settings: Res<Settings>,
+    mut clear_color: ResMut<ClearColor>,
+    mut volume: ResMut<VolumeSettings>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    *clear_color = settings.display.clear_color();
+    *volume = settings.audio;
+
+    if let Ok(mut window) = window_query.get_single_mut() {
+        window.mode = settings.display.window_mode();
+        window.present_mode = settings.display.present_mode();
+    }
+}
@@ -0,0 +1,306 @@
+//! Sprite themes, loaded from `assets/themes/<name>.theme.ron`.
+//!
+//! A theme points at a sprite atlas and says which tile draws the paddle,
+//! the ball, the walls and every brick kind at every damage stage. Anything
+//! the theme leaves out, or a theme that fails to load, keeps the primitive
+//! shapes the entities are spawned with.
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadState, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    sprite::Mesh2dHandle,
+    utils::{BoxedFuture, HashMap},
+};
+use serde::Deserialize;
+
+use super::{Arena, Brick, BrickKind, Settings};
+
+const THEME_DIR: &str = "themes";
+
+/// Behind everything else, still in front of the camera's near plane.
+const BACKGROUND_Z: f32 = -0.09;
+
+pub struct ThemePlugin;
+
+impl Plugin for ThemePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<Theme>()
+            .init_asset_loader::<ThemeLoader>()
+            .add_startup_system(start_loading_theme)
+            .add_system(finish_loading_theme)
+            .add_system(apply_skins.after(finish_loading_theme))
+            .add_system(show_brick_damage.after(apply_skins));
+    }
+}
+
+/// The part of a theme an entity is drawn with.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ThemePart {
+    Paddle,
+    Ball,
+    Wall,
+    Brick(BrickKind),
+}
+
+/// Lets the loaded theme draw the entity. `size` is the size of its
+/// primitive mesh, before the transform scale.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Skin {
+    pub part: ThemePart,
+    pub size: Vec2,
+}
+
+#[derive(Deserialize, TypeUuid, Clone, Debug)]
+#[uuid = "6a3c2a57-1f7e-4c8e-b0f4-52d3c0e9a1b4"]
+pub struct Theme {
+    /// Path of the atlas image, relative to `assets/`.
+    pub atlas: String,
+    pub tile_size: Vec2,
+    pub columns: usize,
+    pub rows: usize,
+    /// Gap between tiles.
+    #[serde(default)]
+    pub padding: Vec2,
+    /// Gap before the first tile.
+    #[serde(default)]
+    pub offset: Vec2,
+    /// Image stretched over the whole arena.
+    #[serde(default)]
+    pub background: Option<String>,
+    #[serde(default)]
+    pub paddle: Option<usize>,
+    #[serde(default)]
+    pub ball: Option<usize>,
+    #[serde(default)]
+    pub wall: Option<usize>,
+    /// Tiles of every brick kind, intact first and then more and more
+    /// damaged.
+    #[serde(default)]
+    pub bricks: HashMap<BrickKind, Vec<usize>>,
+}
+
+impl Theme {
+    fn tile(&self, part: ThemePart) -> Option<usize> {
+        match part {
+            ThemePart::Paddle => self.paddle,
+            ThemePart::Ball => self.ball,
+            ThemePart::Wall => self.wall,
+            ThemePart::Brick(kind) => self.bricks.get(&kind)?.first().copied(),
+        }
+    }
+
+    fn brick_tile(&self, brick: &Brick) -> Option<usize> {
+        let stages = self.bricks.get(&brick.kind)?;
+        let damage = 1. - brick.health() / brick.max_health();
+        let stage = (damage * stages.len() as f32) as usize;
+        stages.get(stage.min(stages.len().checked_sub(1)?)).copied()
+    }
+}
+
+#[derive(Default)]
+struct ThemeLoader;
+
+impl AssetLoader for ThemeLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let theme: Theme = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(theme));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["theme.ron"]
+    }
+}
+
+/// Where loading the theme picked in the settings is at.
+#[derive(Resource)]
+enum ThemeLoading {
+    Definition(Handle<Theme>),
+    Images {
+        theme: Box<Theme>,
+        atlas: Handle<Image>,
+        background: Option<Handle<Image>>,
+    },
+}
+
+/// The theme in use, once it and its images are loaded.
+#[derive(Resource)]
+struct LoadedTheme {
+    theme: Theme,
+    atlas: Handle<TextureAtlas>,
+}
+
+fn start_loading_theme(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    settings: Res<Settings>,
+) {
+    let name = &settings.display.theme;
+    if name.is_empty() {
+        return;
+    }
+
+    let path = format!("{}/{}.theme.ron", THEME_DIR, name);
+    commands.insert_resource(ThemeLoading::Definition(asset_server.load(path)));
+}
+
+#[allow(clippy::too_many_arguments)]
+fn finish_loading_theme(
+    mut commands: Commands,
+    loading: Option<ResMut<ThemeLoading>>,
+    asset_server: Res<AssetServer>,
+    themes: Res<Assets<Theme>>,
+    mut atlases: ResMut<Assets<TextureAtlas>>,
+    arena: Res<Arena>,
+    settings: Res<Settings>,
+) {
+    let Some(mut loading) = loading else {
+        return;
+    };
+
+    match loading.as_mut() {
+        ThemeLoading::Definition(handle) => match asset_server.get_load_state(&*handle) {
+            LoadState::Loaded => {
+                let Some(theme) = themes.get(handle) else {
+                    return;
+                };
+                *loading = ThemeLoading::Images {
+                    theme: Box::new(theme.clone()),
+                    atlas: asset_server.load(theme.atlas.as_str()),
+                    background: theme
+                        .background
+                        .as_ref()
+                        .map(|path| asset_server.load(path.as_str())),
+                };
+            }
+            LoadState::Failed => {
+                warn!(
+                    "Could not load theme {}, using plain shapes",
+                    settings.display.theme
+                );
+                commands.remove_resource::<ThemeLoading>();
+            }
+            _ => (),
+        },
+        ThemeLoading::Images {
+            theme,
+            atlas,
+            background,
+        } => {
+            let background_state = background
+                .as_ref()
+                .map(|handle| asset_server.get_load_state(handle));
+            let atlas_state = asset_server.get_load_state(&*atlas);
+
+            if atlas_state == LoadState::Failed {
+                warn!(
+                    "Could not load the atlas of theme {}, using plain shapes",
+                    settings.display.theme
+                );
+                commands.remove_resource::<ThemeLoading>();
+                return;
+            }
+            if atlas_state != LoadState::Loaded
+                || matches!(
+                    background_state,
+                    Some(LoadState::Loading | LoadState::NotLoaded)
+                )
+            {
+                return;
+            }
+
+            if let (Some(handle), Some(LoadState::Loaded)) = (background, background_state) {
+                spawn_background(&mut commands, handle.clone(), &arena);
+            }
+
+            let atlas = atlases.add(TextureAtlas::from_grid(
+                atlas.clone(),
+                theme.tile_size,
+                theme.columns,
+                theme.rows,
+                Some(theme.padding),
+                Some(theme.offset),
+            ));
+
+            info!("Using theme {}", settings.display.theme);
+            commands.insert_resource(LoadedTheme {
+                theme: theme.as_ref().clone(),
+                atlas,
+            });
+            commands.remove_resource::<ThemeLoading>();
+        }
+    }
+}
+
+fn spawn_background(commands: &mut Commands, image: Handle<Image>, arena: &Arena) {
+    commands.spawn((
+        SpriteBundle {
+            texture: image,
+            sprite: Sprite {
+                custom_size: Some(arena.size),
+                ..default()
+            },
+            transform: Transform::from_translation(Vec3::Z * BACKGROUND_Z),
+            ..default()
+        },
+        Name::new("Background"),
+    ));
+}
+
+/// Swaps the primitive shape of skinned entities for their theme tile.
+fn apply_skins(
+    mut commands: Commands,
+    skins_query: Query<(Entity, &Skin, Option<&Brick>), Without<TextureAtlasSprite>>,
+    theme: Option<Res<LoadedTheme>>,
+) {
+    let Some(theme) = theme else {
+        return;
+    };
+
+    for (entity, skin, brick) in skins_query.iter() {
+        // Bricks of a resumed run can already be damaged.
+        let tile = match brick {
+            Some(brick) => theme.theme.brick_tile(brick),
+            None => theme.theme.tile(skin.part),
+        };
+        let Some(index) = tile else {
+            continue;
+        };
+
+        commands
+            .entity(entity)
+            .remove::<(Mesh2dHandle, Handle<ColorMaterial>)>()
+            .insert((
+                TextureAtlasSprite {
+                    index,
+                    custom_size: Some(skin.size),
+                    ..default()
+                },
+                theme.atlas.clone(),
+            ));
+    }
+}
+
+fn show_brick_damage(
+    mut bricks_query: Query<(&Brick, &mut TextureAtlasSprite), Changed<Brick>>,
+    theme: Option<Res<LoadedTheme>>,
+) {
+    let Some(theme) = theme else {
+        return;
+    };
+
+    for (brick, mut sprite) in bricks_query.iter_mut() {
+        if let Some(index) = theme.theme.brick_tile(brick) {
+            if sprite.index != index {
+                sprite.index = index;
+            }
+        }
+    }
+}
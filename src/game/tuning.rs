@@ -0,0 +1,316 @@
+//! Gameplay numbers, loaded from `assets/tuning.ron` so they can be tweaked
+//! without recompiling.
+//!
+//! The file is an asset: with `watch_for_changes` on, saving it applies the
+//! new values to the running game right away, paddle, ball and bricks
+//! included. Replays and the attract mode keep the values they started with
+//! and pick up the changes once they end. Replays and saves record the
+//! values through [`Tuning::checksum`], and a run whose tuning changes while
+//! playing is no longer recorded. Missing fields keep the defaults below.
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadState, LoadedAsset},
+    ecs::system::SystemParam,
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{bytes::fnv1a, Difficulty, GameState, RUN_STATES};
+
+pub const TUNING_PATH: &str = "tuning.ron";
+
+pub struct TuningPlugin;
+
+impl Plugin for TuningPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<Tuning>()
+            .init_asset_loader::<TuningLoader>()
+            .init_resource::<Tuning>()
+            .add_startup_system(load_tuning)
+            .add_system(apply_tuning.in_base_set(CoreSet::PreUpdate))
+            .add_system(
+                hold_runs_until_tuned
+                    .before(apply_state_transition::<GameState>)
+                    .in_base_set(CoreSet::StateTransitions),
+            );
+    }
+}
+
+/// The values in use. Also the asset type of the tuning file, which is
+/// copied into the resource whenever it is loaded or changes, except while
+/// replaying or in the attract mode.
+#[derive(Resource, Serialize, Deserialize, TypeUuid, Clone, Debug, PartialEq)]
+#[uuid = "0b6c1c3e-8a43-4a2b-9a55-3f1f0c7d2e61"]
+#[serde(default)]
+pub struct Tuning {
+    pub ball_radius: f32,
+    /// Damage dealt to a brick by every hit of the ball.
+    pub ball_damage: f32,
+    /// Speed added to the paddle while boosting.
+    pub paddle_boost_speed: f32,
+    pub brick_size: Vec2,
+    /// Health of most bricks. Silver bricks have twice as much.
+    pub brick_health: f32,
+    /// Consecutive brick hits needed to raise the multiplier by one.
+    pub hits_per_multiplier: u32,
+    pub max_multiplier: i32,
+    /// Points for every second a level is cleared under its par time.
+    pub fast_clear_points_per_second: f32,
+    /// Points for every life left when a level is cleared.
+    pub life_bonus_points: i32,
+    /// Points for leaving a level through the warp gate.
+    pub warp_bonus_points: i32,
+    /// Chance, between `0.` and `1.`, that a destroyed brick drops a power-up
+    /// capsule when none is already falling.
+    pub power_up_chance: f64,
+    /// Distance a capsule falls every tick.
+    pub capsule_speed: f32,
+    /// Distance a laser bolt flies every tick.
+    pub laser_speed: f32,
+    /// Damage dealt to a brick by every laser bolt.
+    pub laser_damage: f32,
+    /// Ticks between two laser shots.
+    pub laser_cooldown_ticks: u32,
+    /// Ticks a served or caught ball waits on the paddle before launching by
+    /// itself.
+    pub catch_release_ticks: u32,
+    /// Distance an enemy drifts down every tick, and sideways at most.
+    pub enemy_speed: f32,
+    /// Ticks between two enemies coming in.
+    pub enemy_spawn_interval_ticks: u32,
+    /// Most enemies in the arena at once.
+    pub max_enemies: u32,
+    /// Points scored for killing an enemy, before the multiplier.
+    pub enemy_points: i32,
+    /// Ticks between two boss attacks.
+    pub boss_attack_interval_ticks: u32,
+    /// Distance a boss projectile travels every tick.
+    pub boss_projectile_speed: f32,
+    /// Points scored for defeating a boss.
+    pub boss_points: i32,
+    pub ball_speed_up: BallSpeedUpTuning,
+    pub easy: DifficultyTuning,
+    pub normal: DifficultyTuning,
+    pub hard: DifficultyTuning,
+    pub insane: DifficultyTuning,
+}
+
+/// What a [`Difficulty`] changes.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DifficultyTuning {
+    pub lives: i32,
+    /// Speed of the ball when it is served.
+    pub ball_speed: f32,
+    /// Speed the ball never goes past, however much it speeds up.
+    pub max_ball_speed: f32,
+    pub paddle_size: Vec2,
+    /// Paddle speed without boost.
+    pub paddle_speed: f32,
+}
+
+/// How the ball speeds up between the moment it is served and the moment
+/// it is lost.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct BallSpeedUpTuning {
+    pub per_second: f32,
+    pub per_paddle_hit: f32,
+    /// Speed gained the first time the ball reaches the top wall.
+    pub top_wall_jump: f32,
+    /// Speed gained the first time the ball hits a brick in the back rows.
+    pub back_rows_jump: f32,
+    /// Number of layout rows, from the top, that count as back rows.
+    pub back_rows: usize,
+}
+
+impl Default for BallSpeedUpTuning {
+    fn default() -> Self {
+        Self {
+            per_second: 0.004,
+            per_paddle_hit: 0.015,
+            top_wall_jump: 0.15,
+            back_rows_jump: 0.15,
+            back_rows: 2,
+        }
+    }
+}
+
+impl Default for Tuning {
+    fn default() -> Self {
+        Self {
+            ball_radius: 10.,
+            ball_damage: 100.,
+            paddle_boost_speed: 2.,
+            brick_size: Vec2::new(50., 20.),
+            brick_health: 100.,
+            hits_per_multiplier: 4,
+            max_multiplier: 8,
+            fast_clear_points_per_second: 20.,
+            life_bonus_points: 500,
+            warp_bonus_points: 10000,
+            power_up_chance: 0.15,
+            capsule_speed: 1.5,
+            laser_speed: 8.,
+            laser_damage: 100.,
+            laser_cooldown_ticks: 20,
+            catch_release_ticks: 180,
+            enemy_speed: 1.,
+            enemy_spawn_interval_ticks: 600,
+            max_enemies: 3,
+            enemy_points: 100,
+            boss_attack_interval_ticks: 150,
+            boss_projectile_speed: 3.5,
+            boss_points: 5000,
+            ball_speed_up: BallSpeedUpTuning::default(),
+            easy: DifficultyTuning {
+                lives: 12,
+                ball_speed: 2.5,
+                max_ball_speed: 3.25,
+                paddle_size: Vec2::new(130., 20.),
+                paddle_speed: 1.5,
+            },
+            normal: DifficultyTuning {
+                lives: 10,
+                ball_speed: 3.,
+                max_ball_speed: 3.75,
+                paddle_size: Vec2::new(100., 20.),
+                paddle_speed: 1.5,
+            },
+            hard: DifficultyTuning {
+                lives: 6,
+                ball_speed: 3.5,
+                max_ball_speed: 3.75,
+                paddle_size: Vec2::new(80., 20.),
+                paddle_speed: 1.75,
+            },
+            insane: DifficultyTuning {
+                lives: 3,
+                ball_speed: 4.5,
+                max_ball_speed: 4.75,
+                paddle_size: Vec2::new(60., 20.),
+                paddle_speed: 3.,
+            },
+        }
+    }
+}
+
+impl Tuning {
+    /// FNV-1a of the values serialized, for replays and saves to tell whether
+    /// they are played with the tuning they were made with.
+    pub fn checksum(&self) -> u32 {
+        let serialized = ron::to_string(self).expect("tuning is always serializable");
+        fnv1a(serialized.into_bytes())
+    }
+
+    pub fn difficulty(&self, difficulty: Difficulty) -> &DifficultyTuning {
+        match difficulty {
+            Difficulty::Easy => &self.easy,
+            Difficulty::Normal => &self.normal,
+            Difficulty::Hard => &self.hard,
+            Difficulty::Insane => &self.insane,
+        }
+    }
+}
+
+#[derive(Default)]
+struct TuningLoader;
+
+impl AssetLoader for TuningLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let tuning: Tuning = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(tuning));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ron"]
+    }
+}
+
+/// Keeps the tuning file loaded, so changes to it are picked up.
+#[derive(Resource)]
+pub struct TuningHandle(pub Handle<Tuning>);
+
+fn load_tuning(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(TuningHandle(asset_server.load(TUNING_PATH)));
+}
+
+/// What tells whether [`Tuning`] is ready to start a run with.
+#[derive(SystemParam)]
+pub struct TuningStatus<'w> {
+    handle: Res<'w, TuningHandle>,
+    asset_server: Res<'w, AssetServer>,
+    assets: Res<'w, Assets<Tuning>>,
+    tuning: Res<'w, Tuning>,
+}
+
+impl<'w> TuningStatus<'w> {
+    /// Whether [`Tuning`] holds the values of the tuning file, or the
+    /// defaults because the file could not be loaded.
+    pub fn is_ready(&self) -> bool {
+        match self.assets.get(&self.handle.0) {
+            Some(loaded) => *loaded == *self.tuning,
+            None => self.asset_server.get_load_state(&self.handle.0) == LoadState::Failed,
+        }
+    }
+}
+
+/// Run condition for [`TuningStatus::is_ready`].
+pub fn tuning_ready(status: TuningStatus) -> bool {
+    status.is_ready()
+}
+
+/// Copies the tuning file into the resource. Replays and the attract mode
+/// are simulated with the same values from start to end, changes wait for
+/// them to finish.
+fn apply_tuning(
+    handle: Res<TuningHandle>,
+    assets: Res<Assets<Tuning>>,
+    state: Res<State<GameState>>,
+    mut tuning: ResMut<Tuning>,
+) {
+    if matches!(state.0, GameState::Replaying | GameState::Demo) {
+        return;
+    }
+
+    if let Some(loaded) = assets.get(&handle.0) {
+        if *tuning != *loaded {
+            info!("Applying gameplay tuning from {}", TUNING_PATH);
+            *tuning = loaded.clone();
+        }
+    }
+}
+
+/// Holds back entering a run until the tuning file is loaded, so no tick is
+/// simulated with the defaults.
+fn hold_runs_until_tuned(
+    status: TuningStatus,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut held: Local<Option<GameState>>,
+) {
+    let ready = status.is_ready();
+    match next_state.0.clone() {
+        Some(next) => {
+            // A newer state change replaces the held one.
+            *held = None;
+            if !ready && RUN_STATES.contains(&next) {
+                next_state.0 = None;
+                *held = Some(next);
+            }
+        }
+        None if ready => {
+            if let Some(next) = held.take() {
+                next_state.set(next);
+            }
+        }
+        None => (),
+    }
+}
@@ -1,24 +1,126 @@
-use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
+use bevy::{
+    prelude::*,
+    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+    utils::HashMap,
+};
+use serde::Deserialize;
 
-use super::GameState;
-
-const DEFAULT_BRICK_SIZE: Vec2 = Vec2 { x: 50., y: 20. };
+use super::{
+    reset_run, Arena, BrickDestroyed, BrickMotion, Collider, ColliderGrid, GameplaySet, Level,
+    LevelAdvanced, LevelCleared, RunState, Skin, ThemePart, Tuning, RUN_STATES,
+};
 
 pub struct WallPlugin;
 
 impl Plugin for WallPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app
-            .add_system(initialize.in_schedule(OnEnter(GameState::Playing)))
-            .add_system(reset.in_schedule(OnExit(GameState::Playing)))
-            .add_system(bricks_health_check.in_set(OnUpdate(GameState::Playing)));
+        app.init_resource::<BrickAssets>()
+            .add_system(apply_tuning.run_if(resource_changed::<Tuning>()))
+            .add_system(
+                move_bricks
+                    .in_set(GameplaySet::Movement)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            )
+            .add_system(
+                bricks_health_check
+                    .in_set(GameplaySet::Cleanup)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            )
+            .add_system(
+                start_next_level
+                    .run_if(on_event::<LevelAdvanced>())
+                    .in_set(GameplaySet::NextLevel)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            );
+
+        for state in RUN_STATES {
+            app.add_system(
+                initialize
+                    .after(reset_run)
+                    .in_schedule(OnEnter(state.clone())),
+            )
+            .add_system(reset.in_schedule(OnExit(state)));
+        }
+    }
+}
+
+/// The kinds of bricks a level layout can place, each with its own layout
+/// character. Silver bricks take two hits to break.
+#[derive(Deserialize, Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum BrickKind {
+    White,
+    Orange,
+    Cyan,
+    Green,
+    Red,
+    Blue,
+    Pink,
+    Yellow,
+    Silver,
+}
+
+impl BrickKind {
+    pub fn from_layout_char(cell: char) -> Option<Self> {
+        match cell {
+            '#' => Some(Self::White),
+            'o' => Some(Self::Orange),
+            'c' => Some(Self::Cyan),
+            'g' => Some(Self::Green),
+            'r' => Some(Self::Red),
+            'b' => Some(Self::Blue),
+            'p' => Some(Self::Pink),
+            'y' => Some(Self::Yellow),
+            's' => Some(Self::Silver),
+            _ => None,
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            Self::White => Color::WHITE,
+            Self::Orange => Color::rgb(1., 0.55, 0.1),
+            Self::Cyan => Color::rgb(0.2, 0.9, 0.9),
+            Self::Green => Color::rgb(0.2, 0.85, 0.2),
+            Self::Red => Color::rgb(0.9, 0.15, 0.15),
+            Self::Blue => Color::rgb(0.2, 0.35, 0.95),
+            Self::Pink => Color::rgb(1., 0.4, 0.75),
+            Self::Yellow => Color::rgb(1., 0.9, 0.1),
+            Self::Silver => Color::SILVER,
+        }
+    }
+
+    /// Points scored for destroying the brick, before the multiplier.
+    pub fn points(&self) -> i32 {
+        match self {
+            Self::White => 50,
+            Self::Orange => 60,
+            Self::Cyan => 70,
+            Self::Green => 80,
+            Self::Red => 90,
+            Self::Blue => 100,
+            Self::Pink => 110,
+            Self::Yellow => 120,
+            Self::Silver => 200,
+        }
+    }
+
+    /// Health of a new brick of this kind, given the tuned brick health.
+    pub fn health(&self, brick_health: f32) -> f32 {
+        match self {
+            Self::Silver => brick_health * 2.,
+            _ => brick_health,
+        }
     }
 }
 
 #[derive(Component)]
 pub struct Brick {
     health: f32,
+    max_health: f32,
+    pub kind: BrickKind,
     pub entity: Entity,
+    /// Row and column of the brick in the level layout.
+    pub cell: (usize, usize),
 }
 
 impl Brick {
@@ -26,25 +128,142 @@ impl Brick {
         self.health -= damage;
     }
 
-    pub fn get_brick_size(&self) -> Vec2 {
-        DEFAULT_BRICK_SIZE
+    pub fn health(&self) -> f32 {
+        self.health
+    }
+
+    /// Health of the brick before it was hit.
+    pub fn max_health(&self) -> f32 {
+        self.max_health
+    }
+
+    pub fn set_health(&mut self, health: f32) {
+        self.health = health;
+    }
+
+    pub fn is_damaged(&self) -> bool {
+        self.health < self.max_health
     }
 }
 
-fn bricks_health_check(bricks_query: Query<&Brick>, mut commands: Commands,) {
-    for brick in bricks_query.iter() {
+/// A brick of a row the level moves.
+#[derive(Component)]
+pub struct MovingBrick {
+    pub motion: BrickMotion,
+    /// How far the brick moved on the last tick.
+    pub velocity: Vec2,
+}
+
+/// Mesh and materials shared by every brick, made the first time they are
+/// needed and kept across levels. Bricks are unit squares scaled to the
+/// brick size, so a single mesh fits them all.
+#[derive(Resource, Default)]
+pub struct BrickAssets {
+    mesh: Option<Mesh2dHandle>,
+    materials: HashMap<BrickKind, Handle<ColorMaterial>>,
+}
+
+impl BrickAssets {
+    pub fn mesh(&mut self, meshes: &mut Assets<Mesh>) -> Mesh2dHandle {
+        self.mesh
+            .get_or_insert_with(|| meshes.add(shape::Quad::new(Vec2::ONE).into()).into())
+            .clone()
+    }
+
+    pub fn material(
+        &mut self,
+        kind: BrickKind,
+        materials: &mut Assets<ColorMaterial>,
+    ) -> Handle<ColorMaterial> {
+        self.materials
+            .entry(kind)
+            .or_insert_with(|| materials.add(ColorMaterial::from(kind.color())))
+            .clone()
+    }
+}
+
+/// Where the brick in a cell of the level layout goes, for the given brick
+/// size.
+fn brick_position(arena: &Arena, size: Vec2, (row, column): (usize, usize)) -> Vec3 {
+    Vec3 {
+        x: -arena.half_size().x + 55. + column as f32 * (size.x / 2. + 40.),
+        y: arena.half_size().y - 40. + (row + 1) as f32 * (size.y / 2. - 45.),
+        z: 0.,
+    }
+}
+
+/// Resizes and moves the bricks when their size is tuned, and scales their
+/// health so damaged bricks stay as damaged.
+fn apply_tuning(
+    mut bricks_query: Query<(&mut Brick, &mut Collider, &mut Transform)>,
+    arena: Res<Arena>,
+    tuning: Res<Tuning>,
+) {
+    for (mut brick, mut collider, mut transform) in bricks_query.iter_mut() {
+        let max_health = brick.kind.health(tuning.brick_health);
+        brick.health *= max_health / brick.max_health;
+        brick.max_health = max_health;
+        collider.size = tuning.brick_size;
+
+        transform.translation = brick_position(&arena, collider.size, brick.cell);
+        transform.scale = collider.size.extend(1.);
+    }
+}
+
+/// Puts moving bricks where their motion has them at this point of the
+/// level.
+fn move_bricks(
+    mut bricks_query: Query<(&Brick, &mut MovingBrick, &Collider, &mut Transform)>,
+    arena: Res<Arena>,
+    run: Res<RunState>,
+) {
+    for (brick, mut moving, collider, mut transform) in bricks_query.iter_mut() {
+        let position = brick_position(&arena, collider.size, brick.cell)
+            + moving.motion.offset(run.level_ticks).extend(0.);
+
+        moving.velocity = (position - transform.translation).truncate();
+        transform.translation = position;
+    }
+}
+
+/// Removes the bricks that ran out of health, and tells when they were the
+/// last ones of the level.
+pub fn bricks_health_check(
+    bricks_query: Query<(&Brick, &Transform)>,
+    mut commands: Commands,
+    mut brick_destroyed_event: EventWriter<BrickDestroyed>,
+    mut level_cleared_event: EventWriter<LevelCleared>,
+    mut run: ResMut<RunState>,
+    mut collider_grid: ResMut<ColliderGrid>,
+) {
+    let mut destroyed = 0;
+
+    for (brick, transform) in bricks_query.iter() {
         if brick.health <= 0. {
+            destroyed += 1;
             commands.entity(brick.entity).despawn_recursive();
+            collider_grid.remove(brick.entity);
+            run.bricks_broken += 1;
+            brick_destroyed_event.send(BrickDestroyed {
+                kind: brick.kind,
+                position: transform.translation.truncate(),
+            });
         }
     }
+
+    if destroyed > 0 && destroyed == bricks_query.iter().count() {
+        level_cleared_event.send(LevelCleared);
+    }
 }
 
 fn reset(
     bricks_query: Query<&Brick>,
     mut commands: Commands,
+    mut collider_grid: ResMut<ColliderGrid>,
 ) {
     for brick in bricks_query.iter() {
         commands.entity(brick.entity).despawn_recursive();
+        collider_grid.remove(brick.entity);
     }
 }
 
@@ -52,56 +271,115 @@ fn initialize(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
-    window_query: Query<&Window>,
+    mut brick_assets: ResMut<BrickAssets>,
+    arena: Res<Arena>,
+    run: Res<RunState>,
+    tuning: Res<Tuning>,
 ) {
-    let window = window_query.get_single().unwrap();
-    let bricks_matrix = vec![vec![1; 19]; 5];
+    let Some(level) = Level::get(run.level) else {
+        return;
+    };
 
-    let first_brick_pos = Vec3 {
-        x: -(window.width() / 2.) + 55.,
-        y: (window.height() / 2.) - 40.,
-        ..Default::default()
+    spawn_bricks(
+        &mut commands,
+        &mut meshes,
+        &mut materials,
+        &mut brick_assets,
+        &arena,
+        &tuning,
+        level,
+    );
+}
+
+/// Swaps the bricks left from the previous level for the bricks of the level
+/// the run moved on to.
+#[allow(clippy::too_many_arguments)]
+fn start_next_level(
+    mut commands: Commands,
+    bricks_query: Query<&Brick>,
+    mut collider_grid: ResMut<ColliderGrid>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut brick_assets: ResMut<BrickAssets>,
+    arena: Res<Arena>,
+    run: Res<RunState>,
+    tuning: Res<Tuning>,
+) {
+    let Some(level) = Level::get(run.level) else {
+        return;
     };
 
-    let mut prev_brick_pos = first_brick_pos;
-    for row in bricks_matrix.iter() {
-        prev_brick_pos = Vec3 {
-            y: prev_brick_pos.y + (DEFAULT_BRICK_SIZE.y / 2.) - 45.,
-            ..first_brick_pos
-        };
-        for (index, _elem) in row.iter().enumerate() {
-            let brick_pos = Vec3 {
-                x: if index == 0 {
-                    first_brick_pos.x
-                } else {
-                    prev_brick_pos = Vec3 {
-                        x: prev_brick_pos.x + (DEFAULT_BRICK_SIZE.x / 2.) + 40.,
-                        ..prev_brick_pos
-                    };
-                    prev_brick_pos.x
-                },
-                y: prev_brick_pos.y,
-                z: 0.,
+    for brick in bricks_query.iter() {
+        commands.entity(brick.entity).despawn_recursive();
+        collider_grid.remove(brick.entity);
+    }
+
+    spawn_bricks(
+        &mut commands,
+        &mut meshes,
+        &mut materials,
+        &mut brick_assets,
+        &arena,
+        &tuning,
+        level,
+    );
+}
+
+fn spawn_bricks(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<ColorMaterial>,
+    brick_assets: &mut BrickAssets,
+    arena: &Arena,
+    tuning: &Tuning,
+    level: &Level,
+) {
+    for (row_index, row) in level.layout.iter().enumerate() {
+        for (index, cell) in row.chars().enumerate() {
+            let Some(kind) = BrickKind::from_layout_char(cell) else {
+                continue;
             };
 
+            let cell = (row_index, index);
             let brick_mesh = commands
                 .spawn(MaterialMesh2dBundle {
-                    mesh: meshes
-                        .add(shape::Quad::new(DEFAULT_BRICK_SIZE).into())
-                        .into(),
-                    material: materials.add(ColorMaterial::from(Color::WHITE)),
-                    transform: Transform::from_translation(brick_pos),
+                    mesh: brick_assets.mesh(meshes),
+                    material: brick_assets.material(kind, materials),
+                    transform: Transform::from_translation(brick_position(
+                        arena,
+                        tuning.brick_size,
+                        cell,
+                    ))
+                    .with_scale(tuning.brick_size.extend(1.)),
                     ..Default::default()
                 })
                 .id();
 
+            let health = kind.health(tuning.brick_health);
             commands
                 .entity(brick_mesh)
                 .insert(Brick {
-                    health: 100.,
+                    health,
+                    max_health: health,
+                    kind,
                     entity: brick_mesh,
+                    cell,
+                })
+                .insert(Collider {
+                    size: tuning.brick_size,
+                })
+                .insert(Skin {
+                    part: ThemePart::Brick(kind),
+                    size: Vec2::ONE,
                 })
                 .insert(Name::new(format!("Brick-{:?}", index)));
+
+            if let Some(motion) = level.motion(row_index) {
+                commands.entity(brick_mesh).insert(MovingBrick {
+                    motion,
+                    velocity: Vec2::ZERO,
+                });
+            }
         }
     }
 }
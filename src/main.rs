@@ -1,24 +1,68 @@
-pub mod game;
-
-use bevy::{prelude::*};
-use bevy_inspector_egui::quick::WorldInspectorPlugin;
-use game::GamePlugin;
+use arkanoid_game::game::{
+    CurrentLevel, GamePlugin, InvalidSettingsFile, Level, Replay, ReplayPlayback, Settings,
+    ARENA_SIZE,
+};
+use bevy::prelude::*;
 
 fn main() {
-    App::new()
-        .add_plugins(DefaultPlugins)
-        .insert_resource(ClearColor(Color::rgb(0.1, 0.1, 0.1)))
-        // .add_plugins(DefaultPlugins.set(WindowPlugin {
-        //     window: WindowDescriptor {
-        //         title: "Arkanoid Game".to_string(),
-        //         resizable: false,
-        //         fit_canvas_to_parent: true,
-        //         ..Default::default()
-        //     },
-        //     ..default()
-        // }))
-        // .add_plugin(WorldInspectorPlugin)
+    let mut app = App::new();
+    let settings = Settings::load().unwrap_or_else(|err| {
+        eprintln!(
+            "Ignoring invalid settings {}: {}",
+            Settings::path().display(),
+            err
+        );
+        app.insert_resource(InvalidSettingsFile);
+        Settings::default()
+    });
+
+    app.add_plugins(
+        DefaultPlugins
+            .set(WindowPlugin {
+                primary_window: Some(Window {
+                    title: "Arkanoid Game".to_string(),
+                    resolution: ARENA_SIZE.into(),
+                    resizable: false,
+                    mode: settings.display.window_mode(),
+                    present_mode: settings.display.present_mode(),
+                    ..default()
+                }),
+                ..default()
+            })
+            // Picks up changes to the tuning file while playing.
+            .set(AssetPlugin {
+                watch_for_changes: true,
+                ..default()
+            }),
+    )
+    .insert_resource(settings.display.clear_color());
+    // .add_plugin(bevy_inspector_egui::quick::WorldInspectorPlugin)
+
+    match Level::get(settings.gameplay.starting_level) {
+        Some(level) => {
+            app.insert_resource(CurrentLevel(level.id));
+        }
+        None => eprintln!(
+            "Unknown starting level {} in settings",
+            settings.gameplay.starting_level
+        ),
+    }
+
+    if let Some(path) = replay_path_from_args() {
+        match Replay::load(&path) {
+            Ok(replay) => {
+                app.insert_resource(ReplayPlayback::new(replay));
+            }
+            Err(err) => eprintln!("Could not load replay {}: {}", path, err),
+        }
+    }
+
+    app.insert_resource(settings.gameplay.difficulty)
+        .insert_resource(settings)
         .add_plugin(GamePlugin)
         .run();
 }
 
+fn replay_path_from_args() -> Option<String> {
+    std::env::args().skip_while(|arg| arg != "--replay").nth(1)
+}